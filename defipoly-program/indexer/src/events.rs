@@ -0,0 +1,141 @@
+// ============================================
+// Event decoding
+// Anchor writes each event as "Program data: <base64>", where the payload
+// is the event's 8-byte discriminator followed by its Borsh encoding.
+// ============================================
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ::defipoly_program::*;
+
+macro_rules! game_events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event `defipoly_program` emits. Variants are named after
+        /// the event structs they wrap.
+        #[allow(clippy::enum_variant_names)]
+        pub enum GameEvent {
+            $($name($name),)*
+        }
+
+        impl GameEvent {
+            /// Decodes one event payload. Returns None for payloads that
+            /// don't match any known discriminator.
+            pub fn decode(data: &[u8]) -> Result<Option<Self>> {
+                $(
+                    if data.starts_with($name::DISCRIMINATOR) {
+                        let mut body = &data[$name::DISCRIMINATOR.len()..];
+                        let event = $name::deserialize(&mut body)
+                            .with_context(|| format!("malformed {}", stringify!($name)))?;
+                        return Ok(Some(GameEvent::$name(event)));
+                    }
+                )*
+                Ok(None)
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(GameEvent::$name(_) => stringify!($name),)*
+                }
+            }
+
+            pub fn header(&self) -> &EventHeader {
+                match self {
+                    $(GameEvent::$name(event) => &event.header,)*
+                }
+            }
+        }
+    };
+}
+
+game_events!(
+    GameInitializedEvent,
+    PropertyInitializedEvent,
+    PlayerInitializedEvent,
+    PlayerAccountClosedEvent,
+    GamePauseEvent,
+    StealChanceUpdatedEvent,
+    StealModeUpdatedEvent,
+    DefenseParamsUpdatedEvent,
+    SetBonusUpdatedEvent,
+    GlobalRatesUpdatedEvent,
+    CooldownClearedEvent,
+    StealCooldownClearedEvent,
+    PropertyBoughtEvent,
+    ShieldActivatedEvent,
+    StealSuccessEvent,
+    StealFailedEvent,
+    RetaliationEvent,
+    InsurancePurchasedEvent,
+    InsurancePayoutEvent,
+    InsuranceClaimedEvent,
+    RewardsClaimedEvent,
+    ReferralCreditEvent,
+    ReferralRewardsClaimedEvent,
+    PropertySoldEvent,
+    AdminUpdateEvent,
+    StealProtectionUpdatedEvent,
+    PropertyStealParamsUpdatedEvent,
+    RewardsVestedEvent,
+    VestedWithdrawnEvent,
+    VestingParamsUpdatedEvent,
+    TokensStakedEvent,
+    TokensUnstakedEvent,
+    StakingParamsUpdatedEvent,
+    SellCurveUpdatedEvent,
+    PropertySellCurveUpdatedEvent,
+    PurchaseLimitsUpdatedEvent,
+    AltPaymentEvent,
+    PaymentLiabilitySettledEvent,
+    AccountMigratedEvent,
+    PaymentMintUpdatedEvent,
+    PaymentRatePostedEvent,
+    TokensBurnedEvent,
+    BurnRateUpdatedEvent,
+    PlayerCrankedEvent,
+    CrankParamsUpdatedEvent,
+    AccumulationTiersUpdatedEvent,
+    ConcentrationCapsUpdatedEvent,
+    PropertyPricingUpdatedEvent,
+    AllowlistUpdatedEvent,
+    PropertyLaunchEvent,
+    AdminGrantEvent,
+    AdminRevokeEvent,
+    AdminShieldGrantEvent,
+    AdminWithdrawEvent,
+    AdminAuthorityTransferEvent,
+);
+
+/// Decodes the events our program emitted from a transaction's log messages.
+/// Tracks the invoke stack so "Program data" lines written by other
+/// programs in the same transaction are skipped.
+pub fn events_from_logs(logs: &[String]) -> Result<Vec<GameEvent>> {
+    let program_id = ::defipoly_program::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let bytes = STANDARD
+                .decode(data.trim())
+                .with_context(|| format!("invalid base64 in log line: {line}"))?;
+            if let Some(event) = GameEvent::decode(&bytes)? {
+                events.push(event);
+            }
+        } else if let Some((id, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                stack.push(id);
+            } else if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,140 @@
+// ============================================
+// Defipoly indexer
+// Replays program events into the backend's SQLite database and checks
+// the result against the chain.
+//
+//   defipoly-indexer [--db PATH] ingest-file <FILE>...
+//   defipoly-indexer [--db PATH] ingest-rpc [--url URL] [--limit N]
+//   defipoly-indexer [--db PATH] verify [--url URL]
+// ============================================
+
+mod events;
+mod source;
+mod store;
+mod verify;
+
+use anyhow::{bail, Context, Result};
+use source::{RpcClient, TxRecord};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use store::Store;
+
+const DEFAULT_DB: &str = "defipoly.db";
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+const DEFAULT_LIMIT: usize = 10_000;
+
+const USAGE: &str = "usage:
+  defipoly-indexer [--db PATH] ingest-file <FILE>...
+  defipoly-indexer [--db PATH] ingest-rpc [--url URL] [--limit N]
+  defipoly-indexer [--db PATH] verify [--url URL]";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<ExitCode> {
+    let mut db = PathBuf::from(DEFAULT_DB);
+    let mut url = DEFAULT_RPC_URL.to_string();
+    let mut limit = DEFAULT_LIMIT;
+    let mut positional = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => db = PathBuf::from(flag_value(&mut args, "--db")?),
+            "--url" => url = flag_value(&mut args, "--url")?,
+            "--limit" => {
+                limit = flag_value(&mut args, "--limit")?
+                    .parse()
+                    .context("--limit expects a number")?
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let Some((command, rest)) = positional.split_first() else {
+        bail!("missing command\n{USAGE}");
+    };
+    let mut store = Store::open(&db).with_context(|| format!("opening {}", db.display()))?;
+
+    match command.as_str() {
+        "ingest-file" => {
+            if rest.is_empty() {
+                bail!("ingest-file needs at least one file\n{USAGE}");
+            }
+            for path in rest {
+                for record in source::read_file(path.as_ref())? {
+                    ingest(&mut store, &record)?;
+                }
+            }
+        }
+        "ingest-rpc" => {
+            let rpc = RpcClient::new(&url);
+            let since = store.latest_signature()?;
+            let signatures = rpc.signatures_since(since.as_deref(), limit)?;
+            println!("{} new transaction(s)", signatures.len());
+            for signature in signatures {
+                if store.is_processed(&signature)? {
+                    continue;
+                }
+                ingest(&mut store, &rpc.transaction(&signature)?)?;
+            }
+        }
+        "verify" => {
+            let mismatches = verify::verify(&store, &RpcClient::new(&url))?;
+            if mismatches > 0 {
+                println!("{mismatches} mismatch(es) between database and chain");
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("database matches chain");
+        }
+        other => bail!("unknown command {other}\n{USAGE}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next().with_context(|| format!("{flag} expects a value"))
+}
+
+fn ingest(store: &mut Store, record: &TxRecord) -> Result<()> {
+    if store.is_processed(&record.signature)? {
+        return Ok(());
+    }
+    // Failed transactions are marked processed so they aren't fetched again,
+    // but nothing they logged is applied
+    let events = if record.failed {
+        Vec::new()
+    } else {
+        events::events_from_logs(&record.logs)
+            .with_context(|| format!("decoding events of {}", record.signature))?
+    };
+
+    let stats = store.apply_transaction(record, &events)?;
+    let names: Vec<&str> = events.iter().map(events::GameEvent::name).collect();
+    println!(
+        "slot {} {}: {} event(s) [{}]",
+        record.slot,
+        record.signature,
+        stats.events,
+        names.join(", ")
+    );
+    if stats.duplicates > 0 {
+        println!("  skipped {} already applied event(s)", stats.duplicates);
+    }
+    if stats.gaps > 0 {
+        println!("  sequence gap before this transaction; see event_gaps");
+    }
+    Ok(())
+}
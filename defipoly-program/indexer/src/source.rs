@@ -0,0 +1,213 @@
+// ============================================
+// Transaction sources
+// Reads transactions from JSON files or a validator's JSON-RPC endpoint
+// ============================================
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+/// The parts of a transaction the indexer needs
+pub struct TxRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: i64,
+    pub logs: Vec<String>,
+    /// Failed transactions still carry logs, but their events never happened
+    pub failed: bool,
+}
+
+impl TxRecord {
+    /// Accepts a `getTransaction` result (json or jsonParsed encoding),
+    /// optionally still wrapped in its JSON-RPC envelope, or a bare log
+    /// record: `{"signature", "slot"?, "blockTime"?, "logs", "err"?}`.
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let value = value.get("result").unwrap_or(value);
+
+        if let Some(meta) = value.get("meta") {
+            let signature = value
+                .pointer("/transaction/signatures/0")
+                .or_else(|| value.get("signature"))
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("transaction has no signature; fetch it with json encoding"))?;
+            return Ok(TxRecord {
+                signature: signature.to_string(),
+                slot: value.get("slot").and_then(Value::as_u64).unwrap_or(0),
+                block_time: value.get("blockTime").and_then(Value::as_i64).unwrap_or(0),
+                logs: string_array(meta.get("logMessages"))?,
+                failed: !meta.get("err").unwrap_or(&Value::Null).is_null(),
+            });
+        }
+
+        let signature = value
+            .get("signature")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("log record has no signature"))?;
+        Ok(TxRecord {
+            signature: signature.to_string(),
+            slot: value.get("slot").and_then(Value::as_u64).unwrap_or(0),
+            block_time: value.get("blockTime").and_then(Value::as_i64).unwrap_or(0),
+            logs: string_array(value.get("logs"))?,
+            failed: !value.get("err").unwrap_or(&Value::Null).is_null(),
+        })
+    }
+}
+
+fn string_array(value: Option<&Value>) -> Result<Vec<String>> {
+    value
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("missing log messages"))?
+        .iter()
+        .map(|line| {
+            line.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("log message is not a string"))
+        })
+        .collect()
+}
+
+/// Loads transactions from a file holding either a JSON array or one JSON
+/// object per line, in the order they should be applied
+pub fn read_file(path: &Path) -> Result<Vec<TxRecord>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("reading {}", path.display()))?;
+
+    if contents.trim_start().starts_with('[') {
+        let values: Vec<Value> = serde_json::from_str(&contents)
+            .with_context(|| format!("parsing {}", path.display()))?;
+        return values.iter().map(TxRecord::from_json).collect();
+    }
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let value: Value = serde_json::from_str(line)
+                .with_context(|| format!("{}:{}", path.display(), index + 1))?;
+            TxRecord::from_json(&value)
+        })
+        .collect()
+}
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        RpcClient {
+            url: url.to_string(),
+            agent: ureq::AgentBuilder::new().build(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(request)
+            .with_context(|| format!("{method} request to {}", self.url))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            bail!("{method} failed: {error}");
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// Signatures of successful program transactions newer than `until`,
+    /// oldest first. At most `limit` are returned, the oldest ones.
+    pub fn signatures_since(&self, until: Option<&str>, limit: usize) -> Result<Vec<String>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        loop {
+            let mut options = json!({ "limit": SIGNATURE_PAGE_SIZE, "commitment": "confirmed" });
+            if let Some(before) = &before {
+                options["before"] = json!(before);
+            }
+            if let Some(until) = until {
+                options["until"] = json!(until);
+            }
+            let page = self.call(
+                "getSignaturesForAddress",
+                json!([defipoly_program::ID.to_string(), options]),
+            )?;
+            let page = page
+                .as_array()
+                .ok_or_else(|| anyhow!("unexpected getSignaturesForAddress result"))?;
+
+            for entry in page {
+                let signature = entry["signature"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("signature entry without signature"))?;
+                if entry["err"].is_null() {
+                    signatures.push(signature.to_string());
+                }
+                before = Some(signature.to_string());
+            }
+            if page.len() < SIGNATURE_PAGE_SIZE {
+                break;
+            }
+        }
+
+        signatures.reverse();
+        signatures.truncate(limit);
+        Ok(signatures)
+    }
+
+    pub fn transaction(&self, signature: &str) -> Result<TxRecord> {
+        let result = self.call(
+            "getTransaction",
+            json!([signature, {
+                "encoding": "json",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0
+            }]),
+        )?;
+        if result.is_null() {
+            bail!("transaction {signature} not found");
+        }
+        TxRecord::from_json(&result)
+    }
+
+    /// Raw data of every program account with exactly `data_size` bytes
+    pub fn program_accounts(&self, data_size: usize) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+        let result = self.call(
+            "getProgramAccounts",
+            json!([defipoly_program::ID.to_string(), {
+                "encoding": "base64",
+                "commitment": "confirmed",
+                "filters": [{ "dataSize": data_size }]
+            }]),
+        )?;
+        result
+            .as_array()
+            .ok_or_else(|| anyhow!("unexpected getProgramAccounts result"))?
+            .iter()
+            .map(|entry| {
+                let pubkey = entry["pubkey"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("account entry without pubkey"))?
+                    .parse::<Pubkey>()
+                    .map_err(|e| anyhow!("invalid pubkey: {e}"))?;
+                Ok((pubkey, decode_account_data(&entry["account"])?))
+            })
+            .collect()
+    }
+}
+
+fn decode_account_data(account: &Value) -> Result<Vec<u8>> {
+    let data = account
+        .pointer("/data/0")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("account without base64 data"))?;
+    Ok(STANDARD.decode(data)?)
+}
@@ -0,0 +1,581 @@
+// ============================================
+// SQLite store
+// Writes into the same tables as the Node backend's defipoly.db and
+// tracks the event sequence to detect missed events.
+// ============================================
+
+use crate::events::GameEvent;
+use crate::source::TxRecord;
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use defipoly_program::{AdminUpdateType, PlayerSnapshot};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+
+// Table definitions match defipoly-backend/src/config/database.js
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS property_ownership (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        wallet_address TEXT NOT NULL,
+        property_id INTEGER NOT NULL,
+        slots_owned INTEGER DEFAULT 0,
+        slots_shielded INTEGER DEFAULT 0,
+        shield_expiry INTEGER DEFAULT 0,
+        purchase_timestamp INTEGER DEFAULT 0,
+        shield_cooldown_duration INTEGER DEFAULT 0,
+        steal_protection_expiry INTEGER DEFAULT 0,
+        bump INTEGER DEFAULT 0,
+        last_updated INTEGER DEFAULT (strftime('%s', 'now')),
+        UNIQUE(wallet_address, property_id)
+    );
+
+    CREATE TABLE IF NOT EXISTS game_actions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        tx_signature TEXT UNIQUE NOT NULL,
+        action_type TEXT NOT NULL,
+        player_address TEXT NOT NULL,
+        property_id INTEGER,
+        target_address TEXT,
+        amount INTEGER,
+        slots INTEGER,
+        success BOOLEAN,
+        metadata TEXT,
+        block_time INTEGER NOT NULL,
+        created_at INTEGER DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE TABLE IF NOT EXISTS player_stats (
+        wallet_address TEXT PRIMARY KEY,
+        total_actions INTEGER DEFAULT 0,
+        properties_bought INTEGER DEFAULT 0,
+        properties_sold INTEGER DEFAULT 0,
+        total_slots_purchased INTEGER DEFAULT 0,
+        successful_steals INTEGER DEFAULT 0,
+        failed_steals INTEGER DEFAULT 0,
+        rewards_claimed INTEGER DEFAULT 0,
+        shields_activated INTEGER DEFAULT 0,
+        total_spent INTEGER DEFAULT 0,
+        total_earned INTEGER DEFAULT 0,
+        total_slots_owned INTEGER DEFAULT 0,
+        daily_income INTEGER DEFAULT 0,
+        leaderboard_score INTEGER DEFAULT 0,
+        roi_ratio REAL DEFAULT 0,
+        steal_win_rate REAL DEFAULT 0,
+        defense_rating REAL DEFAULT 0,
+        complete_sets INTEGER DEFAULT 0,
+        times_stolen INTEGER DEFAULT 0,
+        last_action_time INTEGER,
+        updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE TABLE IF NOT EXISTS properties_state (
+        property_id INTEGER PRIMARY KEY,
+        available_slots INTEGER NOT NULL,
+        max_slots_per_property INTEGER NOT NULL,
+        last_synced INTEGER DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE TABLE IF NOT EXISTS processed_transactions (
+        tx_signature TEXT PRIMARY KEY,
+        block_time INTEGER NOT NULL,
+        event_count INTEGER DEFAULT 0,
+        action_count INTEGER DEFAULT 0,
+        transaction_type TEXT,
+        processed_at INTEGER DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE TABLE IF NOT EXISTS indexer_state (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        last_sequence INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS event_gaps (
+        expected_sequence INTEGER NOT NULL,
+        received_sequence INTEGER NOT NULL,
+        tx_signature TEXT NOT NULL,
+        detected_at INTEGER DEFAULT (strftime('%s', 'now'))
+    );
+";
+
+/// Outcome of applying one transaction
+#[derive(Default)]
+pub struct ApplyStats {
+    pub events: usize,
+    pub duplicates: usize,
+    pub gaps: usize,
+}
+
+struct Action<'a> {
+    action_type: &'a str,
+    player: Pubkey,
+    property_id: Option<u8>,
+    target: Option<Pubkey>,
+    amount: u64,
+    slots: u16,
+    success: Option<bool>,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO indexer_state (id, last_sequence) VALUES (0, 0)",
+            [],
+        )?;
+        Ok(Store { conn })
+    }
+
+    pub fn is_processed(&self, signature: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT 1 FROM processed_transactions WHERE tx_signature = ?1",
+                [signature],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some())
+    }
+
+    /// Signature of the most recently applied transaction, to resume from
+    pub fn latest_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT tx_signature FROM processed_transactions ORDER BY block_time DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Applies every event of `record` atomically. Events at or below the
+    /// last applied sequence number are skipped, so replaying overlapping
+    /// inputs is harmless; a jump in the sequence is recorded as a gap.
+    pub fn apply_transaction(&mut self, record: &TxRecord, events: &[GameEvent]) -> Result<ApplyStats> {
+        let tx = self.conn.transaction()?;
+        let mut stats = ApplyStats::default();
+        let mut last_sequence: u64 =
+            tx.query_row("SELECT last_sequence FROM indexer_state WHERE id = 0", [], |row| row.get(0))?;
+        let mut actions = Vec::new();
+
+        for event in events {
+            let sequence = event.header().sequence;
+            if sequence <= last_sequence {
+                stats.duplicates += 1;
+                continue;
+            }
+            if sequence != last_sequence + 1 {
+                tx.execute(
+                    "INSERT INTO event_gaps (expected_sequence, received_sequence, tx_signature) VALUES (?1, ?2, ?3)",
+                    params![last_sequence + 1, sequence, record.signature],
+                )?;
+                stats.gaps += 1;
+            }
+            last_sequence = sequence;
+            stats.events += 1;
+
+            if let Some(action) = apply_event(&tx, event)? {
+                actions.push(action);
+            }
+        }
+
+        // game_actions holds one row per transaction; a sale that also pays
+        // out rewards is recorded as the sale
+        if actions.len() > 1 {
+            actions.retain(|action| action.action_type != "claim");
+        }
+        if let Some(action) = actions.first() {
+            tx.execute(
+                "INSERT OR IGNORE INTO game_actions
+                    (tx_signature, action_type, player_address, property_id, target_address, amount, slots, success, block_time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    record.signature,
+                    action.action_type,
+                    action.player.to_string(),
+                    action.property_id,
+                    action.target.map(|target| target.to_string()),
+                    action.amount as i64,
+                    action.slots,
+                    action.success,
+                    record.block_time,
+                ],
+            )?;
+        }
+
+        tx.execute(
+            "UPDATE indexer_state SET last_sequence = ?1 WHERE id = 0",
+            [last_sequence],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO processed_transactions
+                (tx_signature, block_time, event_count, action_count, transaction_type)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.signature,
+                record.block_time,
+                stats.events,
+                actions.len().min(1),
+                actions.first().map(|action| action.action_type),
+            ],
+        )?;
+        tx.commit()?;
+
+        Ok(stats)
+    }
+}
+
+/// Updates per-player and per-property state for one event and returns the
+/// player-facing action it represents, if any
+fn apply_event<'a>(tx: &Transaction, event: &GameEvent) -> Result<Option<Action<'a>>> {
+    let timestamp = event.header().timestamp;
+
+    let action = match event {
+        GameEvent::PropertyInitializedEvent(e) => {
+            tx.execute(
+                "INSERT INTO properties_state (property_id, available_slots, max_slots_per_property)
+                 VALUES (?1, ?2, ?2)
+                 ON CONFLICT(property_id) DO UPDATE SET
+                    available_slots = excluded.available_slots,
+                    max_slots_per_property = excluded.max_slots_per_property,
+                    last_synced = strftime('%s', 'now')",
+                params![e.property_id, e.max_slots_per_property],
+            )?;
+            None
+        }
+        GameEvent::PlayerInitializedEvent(e) => {
+            ensure_player(tx, &e.player)?;
+            None
+        }
+        GameEvent::PlayerAccountClosedEvent(e) => {
+            if let Some(wallet) = wallet_for_player_account(tx, &e.player_account)? {
+                tx.execute("DELETE FROM property_ownership WHERE wallet_address = ?1", [&wallet])?;
+                tx.execute(
+                    "UPDATE player_stats SET total_slots_owned = 0, daily_income = 0, complete_sets = 0,
+                        updated_at = strftime('%s', 'now')
+                     WHERE wallet_address = ?1",
+                    [&wallet],
+                )?;
+            }
+            None
+        }
+        GameEvent::PropertyBoughtEvent(e) => {
+            set_slots_owned(tx, &e.player, e.property_id, e.slots_owned)?;
+            set_available_slots(tx, e.property_id, e.available_slots)?;
+            set_player_state(tx, &e.player, &e.player_state)?;
+            record_activity(tx, &e.player, timestamp, &[
+                ("properties_bought", 1),
+                ("total_slots_purchased", e.slots as i64),
+                ("total_spent", e.total_cost as i64),
+            ])?;
+            Some(Action {
+                action_type: "buy",
+                player: e.player,
+                property_id: Some(e.property_id),
+                target: None,
+                amount: e.total_cost,
+                slots: e.slots,
+                success: None,
+            })
+        }
+        GameEvent::PropertySoldEvent(e) => {
+            let (shielded, expiry) = shield_state(tx, &e.player, e.property_id)?;
+            // Mirrors sell_property: an active shield loses the sold slots,
+            // an expired one is cleared
+            let (shielded, expiry) = if shielded == 0 {
+                (0, expiry)
+            } else if timestamp < expiry && e.slots < shielded {
+                (shielded - e.slots, expiry)
+            } else {
+                (0, 0)
+            };
+            set_slots_owned(tx, &e.player, e.property_id, e.slots_owned)?;
+            set_shield(tx, &e.player, e.property_id, shielded.min(e.slots_owned), expiry)?;
+            set_available_slots(tx, e.property_id, e.available_slots)?;
+            set_player_state(tx, &e.player, &e.player_state)?;
+            record_activity(tx, &e.player, timestamp, &[
+                ("properties_sold", 1),
+                ("total_earned", e.received as i64),
+            ])?;
+            Some(Action {
+                action_type: "sell",
+                player: e.player,
+                property_id: Some(e.property_id),
+                target: None,
+                amount: e.received,
+                slots: e.slots,
+                success: None,
+            })
+        }
+        GameEvent::RewardsClaimedEvent(e) => {
+            set_player_state(tx, &e.player, &e.player_state)?;
+            record_activity(tx, &e.player, timestamp, &[
+                ("rewards_claimed", 1),
+                ("total_earned", e.amount as i64),
+            ])?;
+            Some(Action {
+                action_type: "claim",
+                player: e.player,
+                property_id: None,
+                target: None,
+                amount: e.amount,
+                slots: 0,
+                success: None,
+            })
+        }
+        GameEvent::ShieldActivatedEvent(e) => {
+            set_shield(tx, &e.player, e.property_id, e.slots_shielded, e.expiry)?;
+            record_activity(tx, &e.player, timestamp, &[
+                ("shields_activated", 1),
+                ("total_spent", e.cost as i64),
+            ])?;
+            Some(Action {
+                action_type: "shield",
+                player: e.player,
+                property_id: Some(e.property_id),
+                target: None,
+                amount: e.cost,
+                slots: e.slots_shielded,
+                success: None,
+            })
+        }
+        GameEvent::StealSuccessEvent(e) => {
+            apply_steal(tx, &e.attacker, &e.target, e.property_id, e.slots_stolen, timestamp, e.steal_cost)?;
+            set_player_state(tx, &e.attacker, &e.attacker_state)?;
+            set_player_state(tx, &e.target, &e.target_state)?;
+            Some(steal_action(e.attacker, e.target, e.property_id, e.steal_cost, e.slots_stolen))
+        }
+        GameEvent::RetaliationEvent(e) => {
+            apply_steal(tx, &e.attacker, &e.target, e.property_id, e.slots_stolen, timestamp, e.steal_cost)?;
+            set_player_state(tx, &e.attacker, &e.attacker_state)?;
+            set_player_state(tx, &e.target, &e.target_state)?;
+            Some(steal_action(e.attacker, e.target, e.property_id, e.steal_cost, e.slots_stolen))
+        }
+        GameEvent::StealFailedEvent(e) => {
+            apply_steal(tx, &e.attacker, &e.target, e.property_id, 0, timestamp, e.steal_cost)?;
+            Some(steal_action(e.attacker, e.target, e.property_id, e.steal_cost, 0))
+        }
+        GameEvent::InsurancePurchasedEvent(e) => {
+            record_activity(tx, &e.player, timestamp, &[("total_spent", e.premium as i64)])?;
+            None
+        }
+        GameEvent::AdminGrantEvent(e) => {
+            set_slots_owned(tx, &e.target_player, e.property_id, e.slots_owned)?;
+            set_available_slots(tx, e.property_id, e.available_slots)?;
+            set_player_state(tx, &e.target_player, &e.player_state)?;
+            None
+        }
+        GameEvent::AdminRevokeEvent(e) => {
+            let (shielded, expiry) = shield_state(tx, &e.target_player, e.property_id)?;
+            set_slots_owned(tx, &e.target_player, e.property_id, e.slots_owned)?;
+            set_shield(tx, &e.target_player, e.property_id, shielded.min(e.slots_owned), expiry)?;
+            set_available_slots(tx, e.property_id, e.available_slots)?;
+            set_player_state(tx, &e.target_player, &e.player_state)?;
+            None
+        }
+        GameEvent::AdminShieldGrantEvent(e) => {
+            let slots_owned: u16 = tx
+                .query_row(
+                    "SELECT slots_owned FROM property_ownership WHERE wallet_address = ?1 AND property_id = ?2",
+                    params![e.player.to_string(), e.property_id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .unwrap_or(0);
+            set_shield(tx, &e.player, e.property_id, slots_owned, e.expiry)?;
+            None
+        }
+        GameEvent::PlayerCrankedEvent(e) => {
+            // crank_player clears every shield that has run out
+            tx.execute(
+                "UPDATE property_ownership SET slots_shielded = 0, shield_expiry = 0,
+                    last_updated = strftime('%s', 'now')
+                 WHERE wallet_address = ?1 AND shield_expiry != 0 AND shield_expiry <= ?2",
+                params![e.player.to_string(), timestamp],
+            )?;
+            set_player_state(tx, &e.player, &e.player_state)?;
+            None
+        }
+        GameEvent::AdminUpdateEvent(e) if e.update_type == AdminUpdateType::MaxSlots => {
+            // update_property_max_slots moves available slots by the change in max
+            tx.execute(
+                "UPDATE properties_state SET
+                    available_slots = available_slots + (?2 - max_slots_per_property),
+                    max_slots_per_property = ?2,
+                    last_synced = strftime('%s', 'now')
+                 WHERE property_id = ?1",
+                params![e.property_id, e.new_value as i64],
+            )?;
+            None
+        }
+        // Configuration, payment and bookkeeping events don't change the
+        // per-player or per-property state kept here
+        _ => None,
+    };
+
+    Ok(action)
+}
+
+fn steal_action<'a>(attacker: Pubkey, target: Pubkey, property_id: u8, cost: u64, slots_stolen: u16) -> Action<'a> {
+    Action {
+        action_type: if slots_stolen > 0 { "steal_success" } else { "steal_failed" },
+        player: attacker,
+        property_id: Some(property_id),
+        target: Some(target),
+        amount: cost,
+        slots: slots_stolen,
+        success: Some(slots_stolen > 0),
+    }
+}
+
+fn apply_steal(
+    tx: &Transaction,
+    attacker: &Pubkey,
+    target: &Pubkey,
+    property_id: u8,
+    slots_stolen: u16,
+    timestamp: i64,
+    cost: u64,
+) -> Result<()> {
+    if slots_stolen > 0 {
+        let attacker_slots = slots_owned(tx, attacker, property_id)?;
+        set_slots_owned(tx, attacker, property_id, attacker_slots + slots_stolen)?;
+
+        let target_slots = slots_owned(tx, target, property_id)?.saturating_sub(slots_stolen);
+        let (shielded, expiry) = shield_state(tx, target, property_id)?;
+        set_slots_owned(tx, target, property_id, target_slots)?;
+        set_shield(tx, target, property_id, shielded.min(target_slots), expiry)?;
+
+        record_activity(tx, attacker, timestamp, &[("successful_steals", 1), ("total_spent", cost as i64)])?;
+        ensure_player(tx, target)?;
+        tx.execute(
+            "UPDATE player_stats SET times_stolen = times_stolen + 1 WHERE wallet_address = ?1",
+            [target.to_string()],
+        )?;
+    } else {
+        record_activity(tx, attacker, timestamp, &[("failed_steals", 1), ("total_spent", cost as i64)])?;
+    }
+    Ok(())
+}
+
+fn ensure_player(tx: &Transaction, wallet: &Pubkey) -> Result<()> {
+    tx.execute(
+        "INSERT OR IGNORE INTO player_stats (wallet_address) VALUES (?1)",
+        [wallet.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Maps a closed PlayerAccount address back to its wallet
+fn wallet_for_player_account(tx: &Transaction, player_account: &Pubkey) -> Result<Option<String>> {
+    let mut statement = tx.prepare("SELECT wallet_address FROM player_stats")?;
+    let wallets = statement.query_map([], |row| row.get::<_, String>(0))?;
+    for wallet in wallets {
+        let wallet = wallet?;
+        let Ok(key) = wallet.parse::<Pubkey>() else {
+            continue;
+        };
+        let (address, _) = Pubkey::find_program_address(&[b"player", key.as_ref()], &defipoly_program::ID);
+        if address == *player_account {
+            return Ok(Some(wallet));
+        }
+    }
+    Ok(None)
+}
+
+fn set_player_state(tx: &Transaction, wallet: &Pubkey, state: &PlayerSnapshot) -> Result<()> {
+    ensure_player(tx, wallet)?;
+    tx.execute(
+        "UPDATE player_stats SET total_slots_owned = ?2, daily_income = ?3, complete_sets = ?4,
+            updated_at = strftime('%s', 'now')
+         WHERE wallet_address = ?1",
+        params![
+            wallet.to_string(),
+            state.total_slots_owned,
+            state.total_base_daily_income as i64,
+            state.complete_sets_owned,
+        ],
+    )?;
+    Ok(())
+}
+
+fn record_activity(tx: &Transaction, wallet: &Pubkey, timestamp: i64, increments: &[(&str, i64)]) -> Result<()> {
+    ensure_player(tx, wallet)?;
+    let mut sql = String::from("UPDATE player_stats SET total_actions = total_actions + 1, last_action_time = ?2");
+    for (column, _) in increments {
+        sql.push_str(&format!(", {column} = {column} + ?"));
+    }
+    sql.push_str(" WHERE wallet_address = ?1");
+
+    let wallet = wallet.to_string();
+    let mut values: Vec<&dyn rusqlite::ToSql> = vec![&wallet, &timestamp];
+    values.extend(increments.iter().map(|(_, amount)| amount as &dyn rusqlite::ToSql));
+    tx.execute(&sql, values.as_slice())?;
+    Ok(())
+}
+
+fn slots_owned(tx: &Transaction, wallet: &Pubkey, property_id: u8) -> Result<u16> {
+    Ok(tx
+        .query_row(
+            "SELECT slots_owned FROM property_ownership WHERE wallet_address = ?1 AND property_id = ?2",
+            params![wallet.to_string(), property_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(0))
+}
+
+fn shield_state(tx: &Transaction, wallet: &Pubkey, property_id: u8) -> Result<(u16, i64)> {
+    Ok(tx
+        .query_row(
+            "SELECT slots_shielded, shield_expiry FROM property_ownership WHERE wallet_address = ?1 AND property_id = ?2",
+            params![wallet.to_string(), property_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .unwrap_or((0, 0)))
+}
+
+fn set_slots_owned(tx: &Transaction, wallet: &Pubkey, property_id: u8, slots: u16) -> Result<()> {
+    tx.execute(
+        "INSERT INTO property_ownership (wallet_address, property_id, slots_owned)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(wallet_address, property_id) DO UPDATE SET
+            slots_owned = excluded.slots_owned,
+            last_updated = strftime('%s', 'now')",
+        params![wallet.to_string(), property_id, slots],
+    )?;
+    Ok(())
+}
+
+fn set_shield(tx: &Transaction, wallet: &Pubkey, property_id: u8, shielded: u16, expiry: i64) -> Result<()> {
+    tx.execute(
+        "INSERT INTO property_ownership (wallet_address, property_id, slots_shielded, shield_expiry)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(wallet_address, property_id) DO UPDATE SET
+            slots_shielded = excluded.slots_shielded,
+            shield_expiry = excluded.shield_expiry,
+            last_updated = strftime('%s', 'now')",
+        params![wallet.to_string(), property_id, shielded, expiry],
+    )?;
+    Ok(())
+}
+
+fn set_available_slots(tx: &Transaction, property_id: u8, available: u16) -> Result<()> {
+    tx.execute(
+        "UPDATE properties_state SET available_slots = ?2, last_synced = strftime('%s', 'now')
+         WHERE property_id = ?1",
+        params![property_id, available],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,145 @@
+// ============================================
+// Chain verification
+// Compares the indexed state against the live program accounts
+// ============================================
+
+use crate::source::RpcClient;
+use crate::store::Store;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::{anyhow, Result};
+use defipoly_program::{PlayerAccount, Property};
+use rusqlite::{params, OptionalExtension};
+use std::collections::HashSet;
+use std::mem::size_of;
+
+/// Prints every difference between the database and the chain and returns
+/// how many were found
+pub fn verify(store: &Store, rpc: &RpcClient) -> Result<usize> {
+    let mut mismatches = 0;
+    mismatches += verify_properties(store, rpc)?;
+    mismatches += verify_players(store, rpc)?;
+    Ok(mismatches)
+}
+
+fn verify_properties(store: &Store, rpc: &RpcClient) -> Result<usize> {
+    let conn = store.connection();
+    let mut mismatches = 0;
+    let mut seen = HashSet::new();
+
+    for (address, data) in rpc.program_accounts(8 + Property::SIZE)? {
+        let property = Property::try_deserialize(&mut data.as_slice())
+            .map_err(|e| anyhow!("property account {address}: {e}"))?;
+        seen.insert(property.property_id);
+
+        let row: Option<(u16, u16)> = conn
+            .query_row(
+                "SELECT available_slots, max_slots_per_property FROM properties_state WHERE property_id = ?1",
+                [property.property_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let chain = (property.available_slots, property.max_slots_per_property);
+        match row {
+            None => {
+                println!("property {}: missing from database", property.property_id);
+                mismatches += 1;
+            }
+            Some(db) if db != chain => {
+                println!(
+                    "property {}: (available, max) database {:?}, chain {:?}",
+                    property.property_id, db, chain
+                );
+                mismatches += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut statement = conn.prepare("SELECT property_id FROM properties_state")?;
+    for property_id in statement.query_map([], |row| row.get::<_, u8>(0))? {
+        let property_id = property_id?;
+        if !seen.contains(&property_id) {
+            println!("property {property_id}: in database but not on chain");
+            mismatches += 1;
+        }
+    }
+
+    Ok(mismatches)
+}
+
+fn verify_players(store: &Store, rpc: &RpcClient) -> Result<usize> {
+    let conn = store.connection();
+    let mut mismatches = 0;
+    let mut seen = HashSet::new();
+
+    for (address, data) in rpc.program_accounts(8 + size_of::<PlayerAccount>())? {
+        if !data.starts_with(PlayerAccount::DISCRIMINATOR) {
+            continue;
+        }
+        let player: PlayerAccount = bytemuck::pod_read_unaligned(&data[8..]);
+        let wallet = player.owner.to_string();
+        seen.insert(wallet.clone());
+
+        let row: Option<(u16, u64, u8)> = conn
+            .query_row(
+                "SELECT total_slots_owned, daily_income, complete_sets FROM player_stats WHERE wallet_address = ?1",
+                [&wallet],
+                |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64, row.get(2)?)),
+            )
+            .optional()?;
+        let chain = (player.total_slots_owned, player.total_base_daily_income, player.complete_sets_owned);
+        match row {
+            None => {
+                println!("player {wallet} ({address}): missing from database");
+                mismatches += 1;
+                continue;
+            }
+            Some(db) if db != chain => {
+                println!("player {wallet}: (slots, daily income, sets) database {db:?}, chain {chain:?}");
+                mismatches += 1;
+            }
+            Some(_) => {}
+        }
+
+        for property_id in 0..player.property_slots.len() {
+            let db: (u16, u16, i64) = conn
+                .query_row(
+                    "SELECT slots_owned, slots_shielded, shield_expiry FROM property_ownership
+                     WHERE wallet_address = ?1 AND property_id = ?2",
+                    params![wallet, property_id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()?
+                .unwrap_or((0, 0, 0));
+            let chain = (
+                player.property_slots[property_id],
+                player.property_shielded[property_id],
+                player.property_shield_expiry[property_id],
+            );
+            if db.0 == 0 && chain.0 == 0 {
+                continue;
+            }
+            if db != chain {
+                println!(
+                    "player {wallet} property {property_id}: (slots, shielded, shield expiry) database {db:?}, chain {chain:?}"
+                );
+                mismatches += 1;
+            }
+        }
+    }
+
+    let mut statement = conn.prepare(
+        "SELECT DISTINCT wallet_address FROM property_ownership WHERE slots_owned > 0
+         UNION
+         SELECT wallet_address FROM player_stats WHERE total_slots_owned > 0",
+    )?;
+    for wallet in statement.query_map([], |row| row.get::<_, String>(0))? {
+        let wallet = wallet?;
+        if !seen.contains(&wallet) {
+            println!("player {wallet}: holds slots in database but has no account on chain");
+            mismatches += 1;
+        }
+    }
+
+    Ok(mismatches)
+}
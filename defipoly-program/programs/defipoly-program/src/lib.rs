@@ -1,9 +1,13 @@
 // Defipoly Solana Program - v10 Hardened Security
 // Anchor 0.31.1 - Zero-Copy with proper field alignment
 
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
 use anchor_spl::associated_token::AssociatedToken;
+use std::cell::RefMut;
 
 declare_id!("6VQ9vttzEeuP1RktC92E49MQAmekFGJQu1b7XrUEJfnu");
 
@@ -11,38 +15,253 @@ const DEV_WALLET: &str = "CgWTFX7JJQHed3qyMDjJkNCxK4sFe3wbDFABmWAAmrdS";
 const MARKETING_WALLET: &str = "FoPKSQ5HDSVyZgaQobX64YEBVQ2iiKMZp8VHWtd6jLQE";
 const MAX_PROPERTIES: usize = 22;
 const MAX_SETS: usize = 8;
+const MAX_REFERRAL_BPS: u16 = 300; // Referral share is carved out of the 3% marketing cut
+const MAX_REFERRAL_DEPTH: usize = 8; // Referrer links walked when checking for cycles
+const STEAL_MODE_SINGLE: u8 = 0;
+const STEAL_MODE_MULTI: u8 = 1;
+const MAX_STEAL_SLOTS_PER_ATTEMPT: u16 = 10;
+const MAX_RETALIATION_WINDOW_SECONDS: i64 = 7 * 86400;
+const MAX_STEAL_PROTECTION_SECONDS: i64 = 7 * 86400;
+const MAX_STEAL_COOLDOWN_SECONDS: i64 = 7 * 86400;
+const MAX_PURCHASE_COOLDOWN_SECONDS: i64 = 7 * 86400;
+
+// Purchase rate-limit policies (GameConfig::purchase_limit_flags)
+const PURCHASE_LIMIT_PROPERTY: u8 = 1 << 0; // Cooldown between buys of the same property
+const PURCHASE_LIMIT_SET: u8 = 1 << 1; // Set cooldown also applies to the last property bought
+const PURCHASE_LIMIT_GLOBAL: u8 = 1 << 2; // Slot budget per rolling window across all properties
+const PURCHASE_LIMIT_ALL: u8 = PURCHASE_LIMIT_PROPERTY | PURCHASE_LIMIT_SET | PURCHASE_LIMIT_GLOBAL;
+
+// Property pricing modes (Property::pricing_mode)
+const PRICING_FLAT: u8 = 0;
+const PRICING_LINEAR: u8 = 1; // curve_param = price increase per slot sold
+const PRICING_EXPONENTIAL: u8 = 2; // curve_param = price growth in bps per slot sold
+const MAX_CURVE_GROWTH_BPS: u64 = 10000;
+const CURVE_SCALE: u128 = 1_000_000_000_000;
+
+// Launch auction stages (Property::launch_stage)
+const LAUNCH_NONE: u8 = 0;
+const LAUNCH_SCHEDULED: u8 = 1;
+const LAUNCH_AUCTION: u8 = 2;
+const LAUNCH_ENDED: u8 = 3;
+const MAX_LAUNCH_DURATION_SECONDS: i64 = 30 * 86400;
+const MAX_SELL_RAMP_DAYS: u16 = 365;
+const MAX_ACCUMULATION_TIERS: usize = 16;
+const MAX_VESTING_PERIOD_SECONDS: i64 = 365 * 86400;
+const MAX_VESTING_TRANCHES: usize = 3; // Vested claims that can be vesting at once
+const MAX_BURN_BPS: u16 = 5000;
+const EVENT_SCHEMA_VERSION: u8 = 1; // Bumped whenever an event layout changes
+const INCOME_INDEX_SCALE: u128 = 1000; // Fixed-point scale of the per-slot income indexes
+const MAX_CRANK_INTERVAL_SECONDS: i64 = 30 * 86400;
+const LEGACY_PROPERTY_SIZE: usize = 29 + 64; // Property::SIZE before the steal, pricing, launch and sell fields
 
 // ========== HELPER: UPDATE PENDING REWARDS ==========
 
+/// Accrues income since the last update together with the set and
+/// accumulation bonuses earned on it. Runs before every change to a
+/// player's holdings, so bonuses follow the state they were earned in.
 #[inline(never)]
-fn update_pending_rewards(player: &mut PlayerAccount) -> Result<()> {
+fn update_pending_rewards(
+    player: &mut PlayerAccount,
+    game_config: &GameConfig,
+    tiers: &[AccumulationTier],
+) -> Result<()> {
     let clock = Clock::get()?;
-    let clock_timestamp = clock.unix_timestamp;
-    
-    let time_elapsed = clock_timestamp
-        .checked_sub(player.last_accumulation_timestamp)
-        .ok_or(ErrorCode::Overflow)?;
-    
-    if time_elapsed > 0 && player.total_base_daily_income > 0 {
-        let income_per_second = player.total_base_daily_income
-            .checked_div(86400)  // 86400 seconds per day
-            .ok_or(ErrorCode::Overflow)?;
-        
-        let new_rewards = (income_per_second as u128)
-            .checked_mul(time_elapsed as u128)  // time_elapsed is already in seconds
+    accrue_rewards(player, game_config, tiers, clock.unix_timestamp)
+}
+
+/// Set bonuses are earned only on income accrued while the set's mask is
+/// complete, i.e. for the seconds the set was actually held.
+///
+/// Base income is read from each property's income index in `GameConfig`,
+/// so a price or yield change applies from the moment it is made to every
+/// holder, whenever they next settle.
+fn accrue_rewards(
+    player: &mut PlayerAccount,
+    game_config: &GameConfig,
+    tiers: &[AccumulationTier],
+    clock_timestamp: i64,
+) -> Result<()> {
+    let mut base_rewards: u64 = 0;
+    for property_id in 0..MAX_PROPERTIES {
+        let index = income_index_at(game_config, property_id, clock_timestamp)?;
+        let slots = player.property_slots[property_id];
+        if slots > 0 {
+            let accrued = index
+                .checked_sub(player.property_income_index[property_id])
+                .ok_or(ErrorCode::Overflow)?;
+            let income = (accrued as u128)
+                .checked_mul(slots as u128)
+                .and_then(|r| r.checked_div(INCOME_INDEX_SCALE))
+                .and_then(|r| u64::try_from(r).ok())
+                .ok_or(ErrorCode::Overflow)?;
+            base_rewards = base_rewards.checked_add(income).ok_or(ErrorCode::Overflow)?;
+        }
+        player.property_income_index[property_id] = index;
+    }
+
+    if base_rewards > 0 {
+        // Staking boost multiplies base income accrued while the stake is locked
+        let boosted_base = get_boosted_base(player, base_rewards, clock_timestamp)?;
+        let new_rewards = (boosted_base as u128)
+            .checked_mul(player.stake_boost_bps as u128)
+            .and_then(|r| r.checked_div(10000))
             .and_then(|r| u64::try_from(r).ok())
+            .and_then(|boost| base_rewards.checked_add(boost))
             .ok_or(ErrorCode::Overflow)?;
         
+        let pending_before = player.pending_rewards;
         player.pending_rewards = player.pending_rewards
             .checked_add(new_rewards)
             .ok_or(ErrorCode::Overflow)?;
+
+        let mut set_bonus: u64 = 0;
+        for set_id in 0..MAX_SETS as u8 {
+            if is_set_complete(player, set_id) {
+                let bonus = (new_rewards as u128)
+                    .checked_mul(game_config.set_bonus_bps[set_id as usize] as u128)
+                    .and_then(|r| r.checked_div(10000))
+                    .and_then(|r| u64::try_from(r).ok())
+                    .ok_or(ErrorCode::Overflow)?;
+                player.set_bonus_accrued[set_id as usize] = player.set_bonus_accrued[set_id as usize]
+                    .checked_add(bonus)
+                    .ok_or(ErrorCode::Overflow)?;
+                set_bonus = set_bonus.checked_add(bonus).ok_or(ErrorCode::Overflow)?;
+            }
+        }
+
+        // Tier bonus on the slice of the balance this accrual added
+        let accumulation_bonus = calculate_progressive_bonus(player.pending_rewards, tiers)?
+            .saturating_sub(calculate_progressive_bonus(pending_before, tiers)?);
+
+        player.pending_bonus_rewards = player.pending_bonus_rewards
+            .checked_add(set_bonus)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_add(accumulation_bonus)
+            .ok_or(ErrorCode::Overflow)?;
     }
-    
+
+    if clock_timestamp >= player.stake_unlock_at {
+        player.stake_boost_bps = 0;
+    }
+    player.total_base_daily_income = current_daily_income(player, game_config)?;
     player.last_accumulation_timestamp = clock_timestamp;
     
     Ok(())
 }
 
+/// Part of `base_rewards`, accrued since the last settlement, that the stake
+/// boost applies to. An interval the lock ends in is split pro rata by time.
+fn get_boosted_base(player: &PlayerAccount, base_rewards: u64, now: i64) -> Result<u64> {
+    let since = player.last_accumulation_timestamp;
+    let boosted_until = player.stake_unlock_at.min(now);
+    if player.stake_boost_bps == 0 || boosted_until <= since {
+        return Ok(0);
+    }
+    if boosted_until >= now {
+        return Ok(base_rewards);
+    }
+    (base_rewards as u128)
+        .checked_mul(boosted_until.checked_sub(since).ok_or(ErrorCode::Overflow)? as u128)
+        .and_then(|r| r.checked_div(now.checked_sub(since)? as u128))
+        .and_then(|r| u64::try_from(r).ok())
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Income one slot of the property has earned since it was initialized, as
+/// of `now`, scaled by INCOME_INDEX_SCALE
+fn income_index_at(game_config: &GameConfig, property_id: usize, now: i64) -> Result<u64> {
+    let elapsed = now
+        .saturating_sub(game_config.property_income_updated_at[property_id])
+        .max(0);
+    let growth = (game_config.property_income_rate[property_id] as u128)
+        .checked_mul(elapsed as u128)
+        .and_then(|r| r.checked_mul(INCOME_INDEX_SCALE))
+        .and_then(|r| r.checked_div(86400))
+        .and_then(|r| u64::try_from(r).ok())
+        .ok_or(ErrorCode::Overflow)?;
+    game_config.property_income_index[property_id]
+        .checked_add(growth)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Closes the property's income index at `now` under its old rate and
+/// starts accruing at the rate its current price and yield give. Call after
+/// every change to either.
+fn checkpoint_property_income(game_config: &mut GameConfig, property: &Property, now: i64) -> Result<()> {
+    let property_id = property.property_id as usize;
+    game_config.property_income_index[property_id] = income_index_at(game_config, property_id, now)?;
+    game_config.property_income_updated_at[property_id] = now;
+    game_config.property_income_rate[property_id] = daily_income_per_slot(property)?;
+    Ok(())
+}
+
+/// Clears every shield, steal protection, insurance policy and retaliation
+/// window that has run out. Returns how many were cleared.
+fn expire_player_state(player: &mut PlayerAccount, now: i64) -> u8 {
+    let mut expired: u8 = 0;
+    for property_id in 0..MAX_PROPERTIES {
+        if player.property_shield_expiry[property_id] != 0 && player.property_shield_expiry[property_id] <= now {
+            player.property_shielded[property_id] = 0;
+            player.property_shield_expiry[property_id] = 0;
+            expired += 1;
+        }
+        if player.property_steal_protection_expiry[property_id] != 0
+            && player.property_steal_protection_expiry[property_id] <= now
+        {
+            player.property_steal_protection_expiry[property_id] = 0;
+            expired += 1;
+        }
+        if player.property_insurance_expiry[property_id] != 0 && player.property_insurance_expiry[property_id] <= now {
+            player.property_insured_slots[property_id] = 0;
+            player.property_insurance_expiry[property_id] = 0;
+            expired += 1;
+        }
+        if player.property_retaliation_expiry[property_id] != 0
+            && player.property_retaliation_expiry[property_id] <= now
+        {
+            player.property_retaliation_target[property_id] = Pubkey::default();
+            player.property_retaliation_expiry[property_id] = 0;
+            expired += 1;
+        }
+    }
+    expired
+}
+
+/// Daily income of the player's holdings at the current property rates
+fn current_daily_income(player: &PlayerAccount, game_config: &GameConfig) -> Result<u64> {
+    let mut income: u64 = 0;
+    for property_id in 0..MAX_PROPERTIES {
+        income = game_config.property_income_rate[property_id]
+            .checked_mul(player.property_slots[property_id] as u64)
+            .and_then(|i| income.checked_add(i))
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    Ok(income)
+}
+
+/// Empties the player's accrued income and bonus balances for payout
+fn take_pending_rewards(player: &mut PlayerAccount) -> Result<u64> {
+    let total = player.pending_rewards
+        .checked_add(player.pending_bonus_rewards)
+        .ok_or(ErrorCode::Overflow)?;
+
+    player.pending_rewards = 0;
+    player.pending_bonus_rewards = 0;
+    player.total_rewards_claimed = player.total_rewards_claimed
+        .checked_add(total)
+        .ok_or(ErrorCode::Overflow)?;
+
+    Ok(total)
+}
+
+// Anchor 0.31's generated IDL handlers call the deprecated
+// AccountInfo::realloc. They expand next to the program module, so the
+// lint is relaxed around both and restored inside the program itself.
+#[allow(deprecated)]
+mod instructions {
+use super::*;
+
+#[warn(deprecated)]
 #[program]
 pub mod defipoly_program {
     use super::*;
@@ -59,38 +278,18 @@ pub mod defipoly_program {
         game_config.marketing_wallet = MARKETING_WALLET.parse().unwrap();
         game_config.token_mint = ctx.accounts.token_mint.key();
         game_config.reward_pool_vault = ctx.accounts.reward_pool_vault.key();
-        game_config.accumulation_tier1_threshold = 0;
-        game_config.accumulation_tier2_threshold = 0;
-        game_config.accumulation_tier3_threshold = 0;
-        game_config.accumulation_tier4_threshold = 0;
-        game_config.accumulation_tier5_threshold = 0;
-        game_config.accumulation_tier6_threshold = 0;
-        game_config.accumulation_tier7_threshold = 0;
-        game_config.accumulation_tier8_threshold = 0;
-        game_config.steal_chance_bps = 3300; // 33%
-        game_config.steal_cost_percent_bps = 5000; // 50%
-        game_config.set_bonus_bps = [
-            3000,  // Set 0 (Brown) - 30%
-            3286,  // Set 1 (Light Blue) - 32.86%
-            3571,  // Set 2 (Pink) - 35.71%
-            3857,  // Set 3 (Orange) - 38.57%
-            4143,  // Set 4 (Red) - 41.43%
-            4429,  // Set 5 (Yellow) - 44.29%
-            4714,  // Set 6 (Green) - 47.14%
-            5000,  // Set 7 (Dark Blue) - 50%
-        ];
-        game_config.accumulation_tier1_bonus_bps = 0;
-        game_config.accumulation_tier2_bonus_bps = 0;
-        game_config.accumulation_tier3_bonus_bps = 0;
-        game_config.accumulation_tier4_bonus_bps = 0;
-        game_config.accumulation_tier5_bonus_bps = 0;
-        game_config.accumulation_tier6_bonus_bps = 0;
-        game_config.accumulation_tier7_bonus_bps = 0;
-        game_config.accumulation_tier8_bonus_bps = 0;
+        set_default_game_settings(game_config);
         game_config.game_paused = 0;
         game_config.bump = ctx.bumps.game_config;
         game_config.reward_pool_vault_bump = ctx.bumps.reward_pool_vault;
-        game_config._padding = [0u8; 9];
+        game_config._padding = [0u8; 7];
+
+        emit!(GameInitializedEvent {
+            header: next_event_header(game_config)?,
+            authority: game_config.authority,
+            token_mint: game_config.token_mint,
+            reward_pool_vault: game_config.reward_pool_vault,
+        });
 
         Ok(())
     }
@@ -120,26 +319,72 @@ pub mod defipoly_program {
         property.shield_cost_percent_bps = shield_cost_percent_bps;
         property.cooldown_seconds = cooldown_seconds;
         property.bump = ctx.bumps.property;
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.set_total_supply[set_id as usize] = game_config.set_total_supply[set_id as usize]
+            .checked_add(max_slots_per_property as u32)
+            .ok_or(ErrorCode::Overflow)?;
+        game_config.total_supply = game_config.total_supply
+            .checked_add(max_slots_per_property as u32)
+            .ok_or(ErrorCode::Overflow)?;
+        checkpoint_property_income(game_config, property, Clock::get()?.unix_timestamp)?;
+
+        emit!(PropertyInitializedEvent {
+            header: next_event_header(game_config)?,
+            property_id,
+            set_id,
+            max_slots_per_property,
+            max_per_player,
+            price,
+            yield_percent_bps,
+            shield_cost_percent_bps,
+            cooldown_seconds,
+        });
         
         Ok(())
     }
 
-    pub fn initialize_player(ctx: Context<InitializePlayer>) -> Result<()> {
+    /// With a referrer, `remaining_accounts` carries the player accounts of
+    /// the referrer's own referrer chain, nearest first, so it can be checked
+    /// for cycles.
+    pub fn initialize_player<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializePlayer<'info>>,
+        referrer: Option<Pubkey>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         let player = &mut ctx.accounts.player_account.load_init()?;
         let clock = Clock::get()?;
+
+        if is_early_access_active(game_config, clock.unix_timestamp) {
+            verify_allowlist_proof(game_config, &ctx.accounts.player.key(), &allowlist_proof)?;
+        }
         
         player.owner = ctx.accounts.player.key();
+        player.referrer = Pubkey::default();
+        player.referral_rewards_pending = 0;
+        player.total_referral_earnings = 0;
+        player.referral_count = 0;
+        player.insurance_payout_pending = 0;
+        player.purchase_window_start = 0;
+        player.last_claim_timestamp = clock.unix_timestamp;
+        player.purchase_window_slots = 0;
+        player.purchase_window_prev_slots = 0;
+        player.allowlist_slots_used = 0;
         player.total_base_daily_income = 0;
         player.last_accumulation_timestamp = clock.unix_timestamp;
         player.total_rewards_claimed = 0;
         player.pending_rewards = 0;
+        player.pending_bonus_rewards = 0;
         player.total_steals_attempted = 0;
         player.total_steals_successful = 0;
         player.total_slots_owned = 0;
         player.complete_sets_owned = 0;
         player.properties_owned_count = 0;
         player.bump = ctx.bumps.player_account;
-        player._padding1 = [0u8; 3];
+        player.stake_boost_bps = 0;
+        player.stake_unlock_at = 0;
+        player._padding1 = [0u8; 7];
         
         player.property_slots = [0u16; MAX_PROPERTIES];
         player.property_shielded = [0u16; MAX_PROPERTIES];
@@ -149,9 +394,43 @@ pub mod defipoly_program {
         player.property_steal_protection_expiry = [0i64; MAX_PROPERTIES];
         player.set_cooldown_timestamp = [0i64; MAX_SETS];
         player.set_cooldown_duration = [0i64; MAX_SETS];
+        player.set_completed_at = [0i64; MAX_SETS];
+        player.set_bonus_accrued = [0u64; MAX_SETS];
         player.steal_cooldown_timestamp = [0i64; MAX_PROPERTIES];
         player.set_last_purchased_property = [255u8; MAX_SETS];
         player.set_properties_mask = [0u8; MAX_SETS];
+        player.property_retaliation_target = [Pubkey::default(); MAX_PROPERTIES];
+        player.property_retaliation_expiry = [0i64; MAX_PROPERTIES];
+        player.property_insurance_expiry = [0i64; MAX_PROPERTIES];
+        player.property_insured_slots = [0u16; MAX_PROPERTIES];
+        player.property_last_purchase_timestamp = [0i64; MAX_PROPERTIES];
+        player.property_income_index = [0u64; MAX_PROPERTIES];
+        player._padding2 = [0u8; 4];
+
+        // Referrer must be an existing player whose chain doesn't lead back
+        // to this wallet (possible after a close + re-init)
+        if let Some(referrer) = referrer {
+            require!(referrer != ctx.accounts.player.key(), ErrorCode::SelfReferral);
+
+            let referrer_loader = ctx.accounts.referrer_account
+                .as_ref()
+                .ok_or(ErrorCode::InvalidReferrerAccount)?;
+            let mut referrer_account = referrer_loader.load_mut()?;
+
+            require!(referrer_account.owner == referrer, ErrorCode::InvalidReferrerAccount);
+            check_referral_chain(&ctx.accounts.player.key(), referrer_account.referrer, ctx.remaining_accounts)?;
+
+            referrer_account.referral_count = referrer_account.referral_count
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+            player.referrer = referrer;
+        }
+
+        emit!(PlayerInitializedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            referrer: player.referrer,
+        });
         
         Ok(())
     }
@@ -161,119 +440,128 @@ pub mod defipoly_program {
     pub fn buy_property(
         ctx: Context<BuyProperty>,
         slots: u16,
+        max_total_cost: u64,
+        launch_proof: Vec<[u8; 32]>,
+        allowlist_proof: Option<AllowlistProof>,
     ) -> Result<()> {
-        let game_config = &ctx.accounts.game_config.load()?;
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         let property = &mut ctx.accounts.property;
         let player = &mut ctx.accounts.player_account.load_mut()?;
         let clock = Clock::get()?;
 
         require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
 
-        let property_id = property.property_id as usize;
-        let set_id = property.set_id as usize;
-
-        update_pending_rewards(player)?;
-    
-        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
-        require!(slots > 0, ErrorCode::InvalidSlotAmount);
-        require!(property.available_slots >= slots, ErrorCode::NoSlotsAvailable);
-        
-        require!(
-            player.property_slots[property_id].checked_add(slots).ok_or(ErrorCode::Overflow)? <= property.max_per_player,
-            ErrorCode::MaxSlotsReached
-        );
-    
-        // Cooldown check
-        if player.set_cooldown_timestamp[set_id] != 0 {
-            let time_since_last_purchase = clock.unix_timestamp
-                .checked_sub(player.set_cooldown_timestamp[set_id])
-                .ok_or(ErrorCode::Overflow)?;
-            
-            if player.set_last_purchased_property[set_id] != property.property_id {
-                require!(
-                    time_since_last_purchase >= player.set_cooldown_duration[set_id],
-                    ErrorCode::CooldownActive
-                );
-            }
-        }
-    
-        let total_price = property.price
-            .checked_mul(slots as u64)
-            .ok_or(ErrorCode::Overflow)?;
+        let total_price = prepare_purchase(
+            player,
+            property,
+            game_config,
+            &ctx.accounts.accumulation_tiers.entries,
+            slots,
+            &launch_proof,
+            &allowlist_proof,
+            clock.unix_timestamp,
+        )?;
+        require!(total_price <= max_total_cost, ErrorCode::MaxCostExceeded);
+        advance_launch_stage(property, game_config, clock.unix_timestamp)?;
 
-        distribute_payment(
+        let referral_bps = get_referral_bps(player, &ctx.accounts.referrer_account, game_config.referral_bps)?;
+        let referral_amount = distribute_payment(
             total_price,
+            referral_bps,
+            game_config,
             &ctx.accounts.player_token_account,
             &ctx.accounts.reward_pool_vault,
             &ctx.accounts.marketing_token_account,
             &ctx.accounts.dev_token_account,
+            &ctx.accounts.token_mint,
             &ctx.accounts.player,
             &ctx.accounts.token_program,
         )?;
-    
-        property.available_slots = property.available_slots
-            .checked_sub(slots)
-            .ok_or(ErrorCode::Overflow)?;
-    
-        let is_new_ownership = player.property_slots[property_id] == 0;
-        if is_new_ownership {
-            player.property_purchase_timestamp[property_id] = clock.unix_timestamp;
-            player.properties_owned_count = player.properties_owned_count
-                .checked_add(1)
-                .ok_or(ErrorCode::Overflow)?;
-            
-            let property_bit = get_property_bit_in_set(property.property_id, property.set_id);
-            player.set_properties_mask[set_id] |= 1 << property_bit;
-        }
-        
-        player.property_slots[property_id] = player.property_slots[property_id]
-            .checked_add(slots)
-            .ok_or(ErrorCode::Overflow)?;
-    
-        player.total_slots_owned = player.total_slots_owned
-            .checked_add(slots)
-            .ok_or(ErrorCode::Overflow)?;
+        credit_referral(&ctx.accounts.referrer_account, player.owner, referral_amount, game_config)?;
 
-        let daily_income_per_slot = property.price
-            .checked_mul(property.yield_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
+        apply_purchase(player, property, game_config, slots, total_price, clock.unix_timestamp)
+    }
+
+    /// Buys slots paying in a whitelisted alternate mint at its posted rate.
+    /// The whole payment goes to that mint's vault; the fee split, burn and
+    /// referral share only apply to game-token payments. The reward pool's
+    /// share of the price is recorded as owed by the vault, and the vault
+    /// can't be withdrawn from until it has been paid in game tokens.
+    pub fn buy_property_with_mint(
+        ctx: Context<BuyPropertyWithMint>,
+        slots: u16,
+        max_payment_amount: u64,
+        launch_proof: Vec<[u8; 32]>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let property = &mut ctx.accounts.property;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let payment_mint = &mut ctx.accounts.payment_mint_config;
+        let clock = Clock::get()?;
+
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+        require!(payment_mint.enabled != 0, ErrorCode::PaymentMintDisabled);
+        require!(payment_mint.price > 0, ErrorCode::StalePaymentRate);
+        require!(
+            clock.unix_timestamp.saturating_sub(payment_mint.publish_time) <= payment_mint.max_staleness_seconds,
+            ErrorCode::StalePaymentRate
+        );
+
+        let total_price = prepare_purchase(
+            player,
+            property,
+            game_config,
+            &ctx.accounts.accumulation_tiers.entries,
+            slots,
+            &launch_proof,
+            &allowlist_proof,
+            clock.unix_timestamp,
+        )?;
+        advance_launch_stage(property, game_config, clock.unix_timestamp)?;
+
+        let payment_amount = convert_to_payment_amount(
+            total_price,
+            ctx.accounts.token_mint.decimals,
+            payment_mint,
+            ctx.accounts.payment_token_mint.decimals,
+        )?;
+        require!(payment_amount <= max_payment_amount, ErrorCode::SlippageExceeded);
+
+        let vault_balance_before = token_balance(&ctx.accounts.payment_vault.to_account_info())?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.payment_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.player_payment_account.to_account_info(),
+                    mint: ctx.accounts.payment_token_mint.to_account_info(),
+                    to: ctx.accounts.payment_vault.to_account_info(),
+                    authority: ctx.accounts.player.to_account_info(),
+                },
+            ),
+            payment_amount,
+            ctx.accounts.payment_token_mint.decimals,
+        )?;
+        let received = token_balance(&ctx.accounts.payment_vault.to_account_info())?
+            .checked_sub(vault_balance_before)
             .ok_or(ErrorCode::Overflow)?;
-        let total_daily_income_increase = daily_income_per_slot
-            .checked_mul(slots as u64)
+
+        payment_mint.total_received = payment_mint.total_received
+            .checked_add(received)
             .ok_or(ErrorCode::Overflow)?;
-    
-        player.total_base_daily_income = player.total_base_daily_income
-            .checked_add(total_daily_income_increase)
+        payment_mint.reward_pool_owed = payment_mint.reward_pool_owed
+            .checked_add(reward_pool_share(total_price)?)
             .ok_or(ErrorCode::Overflow)?;
-    
-        // Check for complete set bonus
-        let required_properties = get_properties_in_set(property.set_id);
-        let owned_count = count_properties_in_set(player.set_properties_mask[set_id]);
-        let was_complete = player.complete_sets_owned > 0 && is_set_complete(player, property.set_id);
-        
-        if owned_count >= required_properties && !was_complete {
-            player.complete_sets_owned = player.complete_sets_owned
-                .checked_add(1)
-                .ok_or(ErrorCode::Overflow)?;
-        }
-    
-        // Update cooldown
-        player.set_cooldown_timestamp[set_id] = clock.unix_timestamp;
-        player.set_cooldown_duration[set_id] = property.cooldown_seconds;
-        player.set_last_purchased_property[set_id] = property.property_id;
-    
-        emit!(PropertyBoughtEvent {
+
+        emit!(AltPaymentEvent {
+            header: next_event_header(game_config)?,
             player: player.owner,
-            property_id: property.property_id,
-            price: property.price,      
-            slots_owned: player.property_slots[property_id], 
-            slots,
-            total_cost: total_price,
-            total_slots_owned: player.property_slots[property_id],
+            payment_mint: payment_mint.mint,
+            payment_amount: received,
+            game_token_value: total_price,
         });
-    
-        Ok(())
+
+        apply_purchase(player, property, game_config, slots, total_price, clock.unix_timestamp)
     }
 
     // ========== SHIELD SYSTEM ==========
@@ -281,8 +569,9 @@ pub mod defipoly_program {
     pub fn activate_shield(
         ctx: Context<ActivateShield>,
         shield_duration_hours: u16,
+        max_total_cost: u64,
     ) -> Result<()> {
-        let game_config = &ctx.accounts.game_config.load()?;
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         let property = &ctx.accounts.property;
         let player = &mut ctx.accounts.player_account.load_mut()?;
         let clock = Clock::get()?;
@@ -295,7 +584,7 @@ pub mod defipoly_program {
         require!(player.property_slots[property_id] > 0, ErrorCode::DoesNotOwnProperty);
         
         require!(
-            shield_duration_hours >= 1 && shield_duration_hours <= 48,
+            (1..=48).contains(&shield_duration_hours),
             ErrorCode::InvalidShieldDuration
         );
         
@@ -306,45 +595,28 @@ pub mod defipoly_program {
         );
     
         let slots_to_shield = player.property_slots[property_id];
+        let total_cost = get_shield_cost(property, slots_to_shield, shield_duration_hours)?;
+        require!(total_cost <= max_total_cost, ErrorCode::MaxCostExceeded);
     
-        let daily_income_per_slot = property.price
-            .checked_mul(property.yield_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)?;
-        let shield_cost_per_slot = daily_income_per_slot
-            .checked_mul(property.shield_cost_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)?;
-        let cost_per_slot_for_duration = shield_cost_per_slot
-            .checked_mul(shield_duration_hours as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(24)
-            .ok_or(ErrorCode::Overflow)?;
-        let total_cost = cost_per_slot_for_duration
-            .checked_mul(slots_to_shield as u64)
-            .ok_or(ErrorCode::Overflow)?;
-    
-        distribute_payment(
+        let referral_bps = get_referral_bps(player, &ctx.accounts.referrer_account, game_config.referral_bps)?;
+        let referral_amount = distribute_payment(
             total_cost,
+            referral_bps,
+            game_config,
             &ctx.accounts.player_token_account,
             &ctx.accounts.reward_pool_vault,
             &ctx.accounts.marketing_token_account,
             &ctx.accounts.dev_token_account,
+            &ctx.accounts.token_mint,
             &ctx.accounts.player,
             &ctx.accounts.token_program,
         )?;
+        credit_referral(&ctx.accounts.referrer_account, player.owner, referral_amount, game_config)?;
     
         let shield_duration_seconds = (shield_duration_hours as i64)
             .checked_mul(3600)
             .ok_or(ErrorCode::Overflow)?;
-        
-        let shield_start_time = if clock.unix_timestamp < player.property_steal_protection_expiry[property_id] {
-            player.property_steal_protection_expiry[property_id]
-        } else {
-            clock.unix_timestamp
-        };
+        let shield_start_time = get_shield_start(player, property_id, clock.unix_timestamp);
     
         player.property_shielded[property_id] = slots_to_shield;
         player.property_shield_expiry[property_id] = shield_start_time
@@ -355,6 +627,7 @@ pub mod defipoly_program {
             .ok_or(ErrorCode::Overflow)?;
     
         emit!(ShieldActivatedEvent {
+            header: next_event_header(game_config)?,
             player: player.owner,
             property_id: property.property_id,
             slots_shielded: slots_to_shield,
@@ -370,15 +643,31 @@ pub mod defipoly_program {
     pub fn steal_property_instant<'info>(
         ctx: Context<'_, '_, 'info, 'info, StealPropertyInstant<'info>>,
         user_randomness: [u8; 32],
+        slots_to_steal: u16,
+        max_total_cost: u64,
     ) -> Result<()> {
         let property = &ctx.accounts.property;
         let clock = Clock::get()?;
         
         let property_id = property.property_id as usize;
 
-        let (game_paused, steal_cost_percent_bps, steal_chance_bps) = {
+        let set_id = property.set_id as usize;
+
+        let (game_paused, steal_cost_percent_bps, steal_chance_bps, game_referral_bps, retaliation_window, insurance_coverage_bps, cooldown_duration, protection_on_success, protection_on_failure, concentration_caps) = {
             let game_config = ctx.accounts.game_config.load()?;
-            (game_config.game_paused, game_config.steal_cost_percent_bps, game_config.steal_chance_bps)
+            validate_steal_slots(&game_config, slots_to_steal)?;
+            (
+                game_config.game_paused,
+                game_config.steal_cost_percent_bps,
+                game_config.steal_chance_bps,
+                game_config.referral_bps,
+                game_config.retaliation_window_seconds,
+                game_config.insurance_coverage_bps,
+                get_steal_cooldown(&game_config, property)?,
+                get_steal_protection(&game_config, property, true),
+                get_steal_protection(&game_config, property, false),
+                get_concentration_caps(&game_config, set_id),
+            )
         };
 
         require!(game_paused == 0, ErrorCode::GamePaused);
@@ -386,28 +675,13 @@ pub mod defipoly_program {
         let player = &mut ctx.accounts.player_account.load_mut()?;
         require!(player.owner == ctx.accounts.attacker.key(), ErrorCode::Unauthorized);
 
+        let referral_bps = get_referral_bps(player, &ctx.accounts.referrer_account, game_referral_bps)?;
+
         let remaining_accounts = ctx.remaining_accounts;
-        require!(remaining_accounts.len() >= 1, ErrorCode::NoEligibleTargets);
+        require!(!remaining_accounts.is_empty(), ErrorCode::NoEligibleTargets);
 
         // Generate randomness
-        let slot_hashes = &ctx.accounts.slot_hashes;
-        let slot_hashes_data = slot_hashes.data.borrow();
-        
-        require!(
-            slot_hashes_data.len() >= 40,
-            ErrorCode::SlotHashUnavailable
-        );
-
-        let mut slot_hash_bytes = [0u8; 32];
-        slot_hash_bytes.copy_from_slice(&slot_hashes_data[8..40]);
-
-        let mut combined_entropy = [0u8; 32];
-        for i in 0..32 {
-            combined_entropy[i] = user_randomness[i]
-                ^ slot_hash_bytes[i]
-                ^ ((clock.slot >> (i % 8)) as u8)
-                ^ ((clock.unix_timestamp >> (i % 8)) as u8);
-        }
+        let combined_entropy = generate_steal_entropy(&ctx.accounts.slot_hashes, &user_randomness, &clock)?;
         
         let random_u64 = u64::from_le_bytes(combined_entropy[0..8].try_into().unwrap());
 
@@ -432,178 +706,445 @@ pub mod defipoly_program {
             0
         };
         
-        let has_steal_protection = clock.unix_timestamp < target_account.property_steal_protection_expiry[property_id];
-        
         require!(
             target_account.property_slots[property_id] > shielded_slots,
             ErrorCode::AllSlotsShielded
         );
-        require!(!has_steal_protection, ErrorCode::StealProtectionActive);
+        require_no_steal_protection(&target_account, property_id, clock.unix_timestamp)?;
+        require!(
+            player.property_slots[property_id] < property.max_per_player,
+            ErrorCode::MaxSlotsReached
+        );
+        check_concentration(player, set_id, concentration_caps, 1)?;
 
         // Cooldown check
-        let cooldown_duration = property.cooldown_seconds
-            .checked_div(2)
-            .ok_or(ErrorCode::Overflow)?;
         if player.steal_cooldown_timestamp[property_id] != 0 {
             let time_since_last_steal = clock.unix_timestamp
                 .checked_sub(player.steal_cooldown_timestamp[property_id])
                 .ok_or(ErrorCode::Overflow)?;
-            require!(
-                time_since_last_steal >= cooldown_duration,
-                ErrorCode::StealCooldownActive
-            );
+            if time_since_last_steal < cooldown_duration {
+                msg!(
+                    "Steal cooldown active: {} seconds remaining",
+                    cooldown_duration - time_since_last_steal
+                );
+                return err!(ErrorCode::StealCooldownActive);
+            }
         }
 
         player.steal_cooldown_timestamp[property_id] = clock.unix_timestamp;
 
-        let steal_cost = property.price
-            .checked_mul(steal_cost_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)?;
+        let steal_cost = get_steal_cost(property, steal_cost_percent_bps, slots_to_steal)?;
+        require!(steal_cost <= max_total_cost, ErrorCode::MaxCostExceeded);
 
-        distribute_payment(
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let referral_amount = distribute_payment(
             steal_cost,
+            referral_bps,
+            game_config,
             &ctx.accounts.player_token_account,
             &ctx.accounts.reward_pool_vault,
             &ctx.accounts.marketing_token_account,
             &ctx.accounts.dev_token_account,
+            &ctx.accounts.token_mint,
             &ctx.accounts.attacker,
             &ctx.accounts.token_program,
         )?;
 
-        // Determine success
-        let success_threshold = steal_chance_bps as u64;
+        // Determine success - each attempted slot rolls independently
         let success_random = u64::from_le_bytes(combined_entropy[8..16].try_into().unwrap());
-        let success = (success_random % 10000) < success_threshold;
+        let successful_rolls = count_successful_rolls(success_random, slots_to_steal, steal_chance_bps);
+
+        // Capped by the victim's unshielded slots and the attacker's per-player limit
+        let unshielded_slots = target_account.property_slots[property_id]
+            .checked_sub(shielded_slots)
+            .ok_or(ErrorCode::Overflow)?;
+        let attacker_room = property.max_per_player
+            .checked_sub(player.property_slots[property_id])
+            .ok_or(ErrorCode::Overflow)?
+            .min(concentration_room(player, set_id, concentration_caps));
+        let slots_stolen = successful_rolls.min(unshielded_slots).min(attacker_room);
 
         player.total_steals_attempted = player.total_steals_attempted
             .checked_add(1)
             .ok_or(ErrorCode::Overflow)?;
 
-        if success {
-            update_pending_rewards(player)?;
-            update_pending_rewards(&mut target_account)?;
-            
-            target_account.property_slots[property_id] = target_account.property_slots[property_id]
-                .checked_sub(1)
-                .ok_or(ErrorCode::Overflow)?;
-
-            if target_account.property_shielded[property_id] > target_account.property_slots[property_id] {
-                target_account.property_shielded[property_id] = target_account.property_slots[property_id];
-            }
+        if slots_stolen > 0 {
+            update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+            update_pending_rewards(&mut target_account, game_config, &ctx.accounts.accumulation_tiers.entries)?;
 
-            let was_new = player.property_slots[property_id] == 0;
-            if was_new {
-                player.property_purchase_timestamp[property_id] = clock.unix_timestamp;
-                player.properties_owned_count = player.properties_owned_count
-                    .checked_add(1)
-                    .ok_or(ErrorCode::Overflow)?;
-                
-                let set_id = property.set_id as usize;
-                let property_bit = get_property_bit_in_set(property.property_id, property.set_id);
-                player.set_properties_mask[set_id] |= 1 << property_bit;
-            }
-            player.property_slots[property_id] = player.property_slots[property_id]
-                .checked_add(1)
-                .ok_or(ErrorCode::Overflow)?;
+            transfer_stolen_slots(player, &mut target_account, property, game_config, slots_stolen, clock.unix_timestamp)?;
 
-            player.total_slots_owned = player.total_slots_owned
-                .checked_add(1)
-                .ok_or(ErrorCode::Overflow)?;
             player.total_steals_successful = player.total_steals_successful
                 .checked_add(1)
                 .ok_or(ErrorCode::Overflow)?;
 
-            let daily_income_per_slot = property.price
-                .checked_mul(property.yield_percent_bps as u64)
-                .ok_or(ErrorCode::Overflow)?
-                .checked_div(10000)
-                .ok_or(ErrorCode::Overflow)?;
-
-            player.total_base_daily_income = player.total_base_daily_income
-                .checked_add(daily_income_per_slot)
-                .ok_or(ErrorCode::Overflow)?;
+            // Victim may strike back at this attacker for a while
+            if retaliation_window > 0 {
+                target_account.property_retaliation_target[property_id] = ctx.accounts.attacker.key();
+                target_account.property_retaliation_expiry[property_id] = clock.unix_timestamp
+                    .checked_add(retaliation_window)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
 
-            target_account.total_base_daily_income = target_account.total_base_daily_income
-                .checked_sub(daily_income_per_slot)
-                .ok_or(ErrorCode::Overflow)?;
-            
-            target_account.total_slots_owned = target_account.total_slots_owned
-                .checked_sub(1)
-                .ok_or(ErrorCode::Overflow)?;
+            apply_steal_insurance(&mut target_account, property, slots_stolen, insurance_coverage_bps, clock.unix_timestamp, game_config)?;
 
             emit!(StealSuccessEvent {
+                header: next_event_header(game_config)?,
                 attacker: ctx.accounts.attacker.key(),
                 target: target_player,
                 property_id: property.property_id,
                 steal_cost,
                 vrf_result: random_u64,
+                slots_attempted: slots_to_steal,
+                slots_stolen,
+                attacker_state: player_snapshot(player)?,
+                target_state: player_snapshot(&target_account)?,
             });
         } else {
             emit!(StealFailedEvent {
+                header: next_event_header(game_config)?,
                 attacker: ctx.accounts.attacker.key(),
                 target: target_player,
                 property_id: property.property_id,
                 steal_cost,
                 vrf_result: random_u64,
+                slots_attempted: slots_to_steal,
             });
         }
 
-        // Apply 6-hour steal protection
-        let protection_duration: i64 = 6_i64
-            .checked_mul(3600)
-            .ok_or(ErrorCode::Overflow)?;
+        // Failed attempts may grant a shorter protection than successful ones
+        let protection_duration = if slots_stolen > 0 {
+            protection_on_success
+        } else {
+            protection_on_failure
+        };
         target_account.property_steal_protection_expiry[property_id] = clock.unix_timestamp
             .checked_add(protection_duration)
             .ok_or(ErrorCode::Overflow)?;
 
+        // Release the target before crediting, the referrer may be the target
+        drop(target_account);
+        credit_referral(&ctx.accounts.referrer_account, player.owner, referral_amount, game_config)?;
+
         Ok(())
     }
 
-    // ========== CLAIM REWARDS ==========
-
-    pub fn claim_rewards<'info>(
-        ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>,
+    /// Steal back from the player who last stole this property from us.
+    /// Targets that attacker directly, skips the steal cooldown and costs
+    /// `retaliation_cost_bps` of a normal steal. The right is used up
+    /// whether or not the attempt succeeds.
+    pub fn retaliate_steal(
+        ctx: Context<RetaliateSteal>,
+        user_randomness: [u8; 32],
+        slots_to_steal: u16,
+        max_total_cost: u64,
     ) -> Result<()> {
-        let game_config = &ctx.accounts.game_config.load()?;
-        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let property = &ctx.accounts.property;
+        let clock = Clock::get()?;
+
+        let property_id = property.property_id as usize;
+
+        let set_id = property.set_id as usize;
+
+        let (game_paused, steal_cost_percent_bps, steal_chance_bps, game_referral_bps, retaliation_cost_bps, insurance_coverage_bps, protection_on_success, protection_on_failure, concentration_caps) = {
+            let game_config = ctx.accounts.game_config.load()?;
+            validate_steal_slots(&game_config, slots_to_steal)?;
+            (
+                game_config.game_paused,
+                game_config.steal_cost_percent_bps,
+                game_config.steal_chance_bps,
+                game_config.referral_bps,
+                game_config.retaliation_cost_bps,
+                game_config.insurance_coverage_bps,
+                get_steal_protection(&game_config, property, true),
+                get_steal_protection(&game_config, property, false),
+                get_concentration_caps(&game_config, set_id),
+            )
+        };
+
+        require!(game_paused == 0, ErrorCode::GamePaused);
+
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+
+        let mut target_account = ctx.accounts.target_account.load_mut()?;
+        let target_player = target_account.owner;
+
+        require!(target_player != player.owner, ErrorCode::CannotStealFromSelf);
+        require!(
+            player.property_retaliation_target[property_id] == target_player
+                && clock.unix_timestamp < player.property_retaliation_expiry[property_id],
+            ErrorCode::NoRetaliationRight
+        );
+        require!(target_account.property_slots[property_id] > 0, ErrorCode::TargetDoesNotOwnProperty);
+
+        let shielded_slots = if clock.unix_timestamp < target_account.property_shield_expiry[property_id] {
+            target_account.property_shielded[property_id]
+        } else {
+            0
+        };
+        require!(
+            target_account.property_slots[property_id] > shielded_slots,
+            ErrorCode::AllSlotsShielded
+        );
+        require_no_steal_protection(&target_account, property_id, clock.unix_timestamp)?;
+        require!(
+            player.property_slots[property_id] < property.max_per_player,
+            ErrorCode::MaxSlotsReached
+        );
+        check_concentration(player, set_id, concentration_caps, 1)?;
+
+        player.property_retaliation_target[property_id] = Pubkey::default();
+        player.property_retaliation_expiry[property_id] = 0;
+
+        let steal_cost = get_steal_cost(property, steal_cost_percent_bps, slots_to_steal)?
+            .checked_mul(retaliation_cost_bps as u64)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(steal_cost <= max_total_cost, ErrorCode::MaxCostExceeded);
+
+        // The target may be the player's own referrer, in which case it is
+        // already borrowed and gets credited through that borrow
+        let referrer_is_target = ctx.accounts.referrer_account
+            .as_ref()
+            .is_some_and(|referrer| referrer.key() == ctx.accounts.target_account.key());
+        let referral_bps = if !referrer_is_target {
+            get_referral_bps(player, &ctx.accounts.referrer_account, game_referral_bps)?
+        } else if player.referrer == Pubkey::default() {
+            0
+        } else {
+            require!(target_player == player.referrer, ErrorCode::InvalidReferrerAccount);
+            game_referral_bps
+        };
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let referral_amount = distribute_payment(
+            steal_cost,
+            referral_bps,
+            game_config,
+            &ctx.accounts.player_token_account,
+            &ctx.accounts.reward_pool_vault,
+            &ctx.accounts.marketing_token_account,
+            &ctx.accounts.dev_token_account,
+            &ctx.accounts.token_mint,
+            &ctx.accounts.player,
+            &ctx.accounts.token_program,
+        )?;
+        if referrer_is_target {
+            credit_referrer(&mut target_account, player.owner, referral_amount, game_config)?;
+        } else {
+            credit_referral(&ctx.accounts.referrer_account, player.owner, referral_amount, game_config)?;
+        }
+
+        let combined_entropy = generate_steal_entropy(&ctx.accounts.slot_hashes, &user_randomness, &clock)?;
+        let random_u64 = u64::from_le_bytes(combined_entropy[8..16].try_into().unwrap());
+        let successful_rolls = count_successful_rolls(random_u64, slots_to_steal, steal_chance_bps);
+
+        let unshielded_slots = target_account.property_slots[property_id]
+            .checked_sub(shielded_slots)
+            .ok_or(ErrorCode::Overflow)?;
+        let attacker_room = property.max_per_player
+            .checked_sub(player.property_slots[property_id])
+            .ok_or(ErrorCode::Overflow)?
+            .min(concentration_room(player, set_id, concentration_caps));
+        let slots_stolen = successful_rolls.min(unshielded_slots).min(attacker_room);
+
+        player.total_steals_attempted = player.total_steals_attempted
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        if slots_stolen > 0 {
+            update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+            update_pending_rewards(&mut target_account, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+            transfer_stolen_slots(player, &mut target_account, property, game_config, slots_stolen, clock.unix_timestamp)?;
+
+            player.total_steals_successful = player.total_steals_successful
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+
+            apply_steal_insurance(&mut target_account, property, slots_stolen, insurance_coverage_bps, clock.unix_timestamp, game_config)?;
+        }
+
+        let protection_duration = if slots_stolen > 0 {
+            protection_on_success
+        } else {
+            protection_on_failure
+        };
+        target_account.property_steal_protection_expiry[property_id] = clock.unix_timestamp
+            .checked_add(protection_duration)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(RetaliationEvent {
+            header: next_event_header(game_config)?,
+            attacker: player.owner,
+            target: target_player,
+            property_id: property.property_id,
+            steal_cost,
+            vrf_result: random_u64,
+            slots_attempted: slots_to_steal,
+            slots_stolen,
+            attacker_state: player_snapshot(player)?,
+            target_state: player_snapshot(&target_account)?,
+        });
+
+        Ok(())
+    }
+
+    // ========== STEAL INSURANCE ==========
+
+    /// Insures every slot currently held of a property. While the policy
+    /// is active, each stolen slot credits `insurance_coverage_bps` of the
+    /// slot price to the player's claimable insurance balance.
+    pub fn buy_steal_insurance(
+        ctx: Context<BuyStealInsurance>,
+        duration_hours: u16,
+        max_total_cost: u64,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let property = &ctx.accounts.property;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let clock = Clock::get()?;
+
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+
+        let property_id = property.property_id as usize;
+
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(game_config.insurance_coverage_bps > 0, ErrorCode::InsuranceDisabled);
+        require!(player.property_slots[property_id] > 0, ErrorCode::DoesNotOwnProperty);
+        require!(
+            (1..=168).contains(&duration_hours),
+            ErrorCode::InvalidInsuranceDuration
+        );
+        require!(
+            clock.unix_timestamp >= player.property_insurance_expiry[property_id],
+            ErrorCode::InsuranceAlreadyActive
+        );
+
+        let slots_to_insure = player.property_slots[property_id];
+
+        let daily_premium_per_slot = property.price
+            .checked_mul(game_config.insurance_premium_bps as u64)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::Overflow)?;
+        let total_premium = daily_premium_per_slot
+            .checked_mul(duration_hours as u64)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(24)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_mul(slots_to_insure as u64)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(total_premium <= max_total_cost, ErrorCode::MaxCostExceeded);
+
+        let referral_bps = get_referral_bps(player, &ctx.accounts.referrer_account, game_config.referral_bps)?;
+        let referral_amount = distribute_payment(
+            total_premium,
+            referral_bps,
+            game_config,
+            &ctx.accounts.player_token_account,
+            &ctx.accounts.reward_pool_vault,
+            &ctx.accounts.marketing_token_account,
+            &ctx.accounts.dev_token_account,
+            &ctx.accounts.token_mint,
+            &ctx.accounts.player,
+            &ctx.accounts.token_program,
+        )?;
+        credit_referral(&ctx.accounts.referrer_account, player.owner, referral_amount, game_config)?;
+
+        let duration_seconds = (duration_hours as i64)
+            .checked_mul(3600)
+            .ok_or(ErrorCode::Overflow)?;
+
+        player.property_insured_slots[property_id] = slots_to_insure;
+        player.property_insurance_expiry[property_id] = clock.unix_timestamp
+            .checked_add(duration_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(InsurancePurchasedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            property_id: property.property_id,
+            slots_insured: slots_to_insure,
+            premium: total_premium,
+            expiry: player.property_insurance_expiry[property_id],
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_insurance_payout(ctx: Context<ClaimInsurancePayout>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+
+        // Already reserved in the pool when credited
+        let amount = player.insurance_payout_pending;
+        require!(amount > 0, ErrorCode::NoRewardsToClaim);
+        require!(
+            ctx.accounts.reward_pool_vault.amount >= amount,
+            ErrorCode::InsufficientRewardPool
+        );
+
+        let game_config_key = ctx.accounts.game_config.key();
+        let seeds = &[
+            b"reward_pool_vault",
+            game_config_key.as_ref(),
+            &[game_config.reward_pool_vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.reward_pool_vault.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: ctx.accounts.reward_pool_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        player.insurance_payout_pending = 0;
+        game_config.total_insurance_liability = game_config.total_insurance_liability
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(InsuranceClaimedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // ========== CLAIM REWARDS ==========
+
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
         let clock = Clock::get()?;
     
         require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
         require!(game_config.game_paused == 0, ErrorCode::GamePaused);
         
         
-        update_pending_rewards(player)?;
-        
-        let base_rewards = player.pending_rewards;
-        
-        require!(base_rewards > 0, ErrorCode::NoRewardsToClaim);
-        
-        // Calculate accumulation tier bonus
-        let accumulation_bonus = calculate_progressive_bonus(base_rewards, &game_config)?;
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
         
-        // Calculate set completion bonus
-        let mut set_bonus: u64 = 0;
-        for set_id in 0..8u8 {
-            if is_set_complete(player, set_id) {
-                let bonus = (base_rewards as u128)
-                    .checked_mul(game_config.set_bonus_bps[set_id as usize] as u128)
-                    .and_then(|r| r.checked_div(10000))
-                    .and_then(|r| u64::try_from(r).ok())
-                    .ok_or(ErrorCode::Overflow)?;
-                set_bonus = set_bonus.checked_add(bonus).ok_or(ErrorCode::Overflow)?;
-            }
-        }
+        // Set and accumulation bonuses were accrued alongside the income
+        let total_rewards = take_pending_rewards(player)?;
         
-        let total_rewards = base_rewards
-            .checked_add(accumulation_bonus)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_add(set_bonus)
-            .ok_or(ErrorCode::Overflow)?;
+        require!(total_rewards > 0, ErrorCode::NoRewardsToClaim);
         
         require!(
-            ctx.accounts.reward_pool_vault.amount >= total_rewards,
+            available_reward_pool(&ctx.accounts.reward_pool_vault, game_config) >= total_rewards,
             ErrorCode::InsufficientRewardPool
         );
         
@@ -617,77 +1158,111 @@ pub mod defipoly_program {
     
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.reward_pool_vault.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
                 to: ctx.accounts.player_token_account.to_account_info(),
                 authority: ctx.accounts.reward_pool_vault.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, total_rewards)?;
+        token_interface::transfer_checked(transfer_ctx, total_rewards, ctx.accounts.token_mint.decimals)?;
     
-        player.pending_rewards = 0;
-        player.total_rewards_claimed = player.total_rewards_claimed
-            .checked_add(total_rewards)
-            .ok_or(ErrorCode::Overflow)?;
         player.last_accumulation_timestamp = clock.unix_timestamp;
+        let seconds_elapsed = clock.unix_timestamp
+            .checked_sub(player.last_claim_timestamp)
+            .ok_or(ErrorCode::Overflow)?;
+        player.last_claim_timestamp = clock.unix_timestamp;
     
         emit!(RewardsClaimedEvent {
+            header: next_event_header(game_config)?,
             player: player.owner,
             amount: total_rewards,
-            seconds_elapsed: 0,
+            seconds_elapsed,
+            player_state: player_snapshot(player)?,
         });
     
         Ok(())
     }
 
-    // ========== SELL PROPERTY ==========
-
-    pub fn sell_property(
-        ctx: Context<SellProperty>,
-        slots: u16,
-    ) -> Result<()> {
-        let game_config = &ctx.accounts.game_config.load()?;
-        let property = &mut ctx.accounts.property;
+    /// Claims pending rewards into a linear vesting schedule instead of
+    /// paying them out, with `vesting_bonus_bps` on top. Each claim vests on
+    /// its own schedule; up to MAX_VESTING_TRANCHES can be vesting at once.
+    pub fn claim_rewards_vested(ctx: Context<ClaimRewardsVested>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         let player = &mut ctx.accounts.player_account.load_mut()?;
         let clock = Clock::get()?;
-        
+
         require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
-        
-        let property_id = property.property_id as usize;
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(game_config.vesting_period_seconds > 0, ErrorCode::VestingDisabled);
 
-        update_pending_rewards(player)?;
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
 
-        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
-        require!(player.property_slots[property_id] >= slots, ErrorCode::InsufficientSlots);
+        let base_rewards = take_pending_rewards(player)?;
+        require!(base_rewards > 0, ErrorCode::NoRewardsToClaim);
 
-        let time_held = clock.unix_timestamp
-            .checked_sub(player.property_purchase_timestamp[property_id])
+        let vesting_bonus = base_rewards
+            .checked_mul(game_config.vesting_bonus_bps as u64)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(10000)
             .ok_or(ErrorCode::Overflow)?;
-        let days_held = time_held
-            .checked_div(86400)
+        let amount = base_rewards
+            .checked_add(vesting_bonus)
             .ok_or(ErrorCode::Overflow)?;
-        let additional_percent = if days_held >= 14 {
-            1500u16
-        } else {
-            let scaled = (days_held as u64)
-                .checked_mul(1500)
-                .ok_or(ErrorCode::Overflow)?
-                .checked_div(14)
-                .ok_or(ErrorCode::Overflow)?;
-            scaled as u16
-        };
-        let sell_value_bps = 1500u16
-            .checked_add(additional_percent)
+
+        require!(
+            available_reward_pool(&ctx.accounts.reward_pool_vault, game_config) >= amount,
+            ErrorCode::InsufficientRewardPool
+        );
+
+        let vesting = &mut ctx.accounts.vesting_account;
+        if vesting.owner == Pubkey::default() {
+            vesting.owner = player.owner;
+            vesting.bump = ctx.bumps.vesting_account;
+        }
+        settle_vesting(vesting, clock.unix_timestamp)?;
+        let end_time = clock.unix_timestamp
+            .checked_add(game_config.vesting_period_seconds)
             .ok_or(ErrorCode::Overflow)?;
+        add_vesting_tranche(vesting, amount, end_time)?;
 
-        let total_value = property.price
-            .checked_mul(slots as u64)
+        game_config.total_vesting_liability = game_config.total_vesting_liability
+            .checked_add(amount)
             .ok_or(ErrorCode::Overflow)?;
-        let player_receives = total_value
-            .checked_mul(sell_value_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
+        player.total_rewards_claimed = player.total_rewards_claimed
+            .checked_add(vesting_bonus)
+            .ok_or(ErrorCode::Overflow)?;
+        player.last_accumulation_timestamp = clock.unix_timestamp;
+        player.last_claim_timestamp = clock.unix_timestamp;
+
+        emit!(RewardsVestedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            amount: base_rewards,
+            vesting_bonus,
+            end_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw_vested(ctx: Context<WithdrawVested>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let vesting = &mut ctx.accounts.vesting_account;
+        let clock = Clock::get()?;
+
+        settle_vesting(vesting, clock.unix_timestamp)?;
+
+        let amount = vesting.released_amount;
+        require!(amount > 0, ErrorCode::NothingVested);
+
+        vesting.released_amount = 0;
+        vesting.total_withdrawn = vesting.total_withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        game_config.total_vesting_liability = game_config.total_vesting_liability
+            .checked_sub(amount)
             .ok_or(ErrorCode::Overflow)?;
 
         let game_config_key = ctx.accounts.game_config.key();
@@ -700,29 +1275,162 @@ pub mod defipoly_program {
 
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.reward_pool_vault.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
                 to: ctx.accounts.player_token_account.to_account_info(),
                 authority: ctx.accounts.reward_pool_vault.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, player_receives)?;
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.token_mint.decimals)?;
 
-        player.property_slots[property_id] = player.property_slots[property_id]
-            .checked_sub(slots)
-            .ok_or(ErrorCode::Overflow)?;
-        
-        if player.property_shielded[property_id] > 0 {
-            if clock.unix_timestamp < player.property_shield_expiry[property_id] {
-                if slots >= player.property_shielded[property_id] {
-                    player.property_shielded[property_id] = 0;
-                    player.property_shield_expiry[property_id] = 0;
-                } else {
-                    player.property_shielded[property_id] = player.property_shielded[property_id]
-                        .checked_sub(slots)
-                        .ok_or(ErrorCode::Overflow)?;
-                }
+        emit!(VestedWithdrawnEvent {
+            header: next_event_header(game_config)?,
+            player: vesting.owner,
+            amount,
+            remaining_locked: vesting.locked_amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+
+        // Already reserved in the pool when credited
+        let amount = player.referral_rewards_pending;
+        require!(amount > 0, ErrorCode::NoRewardsToClaim);
+        require!(
+            ctx.accounts.reward_pool_vault.amount >= amount,
+            ErrorCode::InsufficientRewardPool
+        );
+
+        let game_config_key = ctx.accounts.game_config.key();
+        let seeds = &[
+            b"reward_pool_vault",
+            game_config_key.as_ref(),
+            &[game_config.reward_pool_vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.reward_pool_vault.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: ctx.accounts.reward_pool_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        player.referral_rewards_pending = 0;
+        game_config.total_referral_liability = game_config.total_referral_liability
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ReferralRewardsClaimedEvent {
+            header: next_event_header(game_config)?,
+            referrer: player.owner,
+            amount,
+            total_referral_earnings: player.total_referral_earnings,
+        });
+
+        Ok(())
+    }
+
+    // ========== SELL PROPERTY ==========
+
+    /// Sells slots back to the pool. With `claim_rewards` the player's
+    /// pending rewards go out in the same transfer as the sale proceeds.
+    pub fn sell_property(
+        ctx: Context<SellProperty>,
+        slots: u16,
+        min_received: u64,
+        claim_rewards: bool,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let property = &mut ctx.accounts.property;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let clock = Clock::get()?;
+        
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+        
+        let property_id = property.property_id as usize;
+
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(player.property_slots[property_id] >= slots, ErrorCode::InsufficientSlots);
+
+        let (player_receives, sell_value_bps, days_held) =
+            get_sell_proceeds(game_config, property, player, slots, clock.unix_timestamp)?;
+        require!(player_receives >= min_received, ErrorCode::MinReceivedNotMet);
+
+        let rewards_paid = if claim_rewards { take_pending_rewards(player)? } else { 0 };
+        let total_payout = player_receives
+            .checked_add(rewards_paid)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            available_reward_pool(&ctx.accounts.reward_pool_vault, game_config) >= total_payout,
+            ErrorCode::InsufficientRewardPool
+        );
+
+        let game_config_key = ctx.accounts.game_config.key();
+        let seeds = &[
+            b"reward_pool_vault",
+            game_config_key.as_ref(),
+            &[game_config.reward_pool_vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.reward_pool_vault.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: ctx.accounts.reward_pool_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(transfer_ctx, total_payout, ctx.accounts.token_mint.decimals)?;
+
+        if rewards_paid > 0 {
+            let seconds_elapsed = clock.unix_timestamp
+                .checked_sub(player.last_claim_timestamp)
+                .ok_or(ErrorCode::Overflow)?;
+            player.last_claim_timestamp = clock.unix_timestamp;
+
+            emit!(RewardsClaimedEvent {
+                header: next_event_header(game_config)?,
+                player: player.owner,
+                amount: rewards_paid,
+                seconds_elapsed,
+                player_state: player_snapshot(player)?,
+            });
+        }
+
+        player.property_slots[property_id] = player.property_slots[property_id]
+            .checked_sub(slots)
+            .ok_or(ErrorCode::Overflow)?;
+        
+        if player.property_shielded[property_id] > 0 {
+            if clock.unix_timestamp < player.property_shield_expiry[property_id] {
+                if slots >= player.property_shielded[property_id] {
+                    player.property_shielded[property_id] = 0;
+                    player.property_shield_expiry[property_id] = 0;
+                } else {
+                    player.property_shielded[property_id] = player.property_shielded[property_id]
+                        .checked_sub(slots)
+                        .ok_or(ErrorCode::Overflow)?;
+                }
             } else {
                 player.property_shielded[property_id] = 0;
                 player.property_shield_expiry[property_id] = 0;
@@ -732,6 +1440,9 @@ pub mod defipoly_program {
         if player.property_shielded[property_id] > player.property_slots[property_id] {
             player.property_shielded[property_id] = player.property_slots[property_id];
         }
+        if player.property_insured_slots[property_id] > player.property_slots[property_id] {
+            player.property_insured_slots[property_id] = player.property_slots[property_id];
+        }
 
         if player.property_slots[property_id] == 0 {
             let set_id = property.set_id as usize;
@@ -740,6 +1451,7 @@ pub mod defipoly_program {
             player.properties_owned_count = player.properties_owned_count
                 .checked_sub(1)
                 .ok_or(ErrorCode::Overflow)?;
+            refresh_set_completion(player, set_id, clock.unix_timestamp)?;
         }
 
         property.available_slots = property.available_slots
@@ -749,26 +1461,252 @@ pub mod defipoly_program {
             .checked_sub(slots)
             .ok_or(ErrorCode::Overflow)?;
 
-        let daily_income_per_slot = property.price
-            .checked_mul(property.yield_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)?;
-        let total_daily_income_decrease = daily_income_per_slot
-            .checked_mul(slots as u64)
-            .ok_or(ErrorCode::Overflow)?;
-    
-        player.total_base_daily_income = player.total_base_daily_income
-            .checked_sub(total_daily_income_decrease)
-            .ok_or(ErrorCode::Overflow)?;
+        player.total_base_daily_income = current_daily_income(player, game_config)?;
 
         emit!(PropertySoldEvent {
+            header: next_event_header(game_config)?,
             player: player.owner,
             property_id: property.property_id,
             slots,
             received: player_receives,
             sell_value_percent: sell_value_bps,
             days_held,
+            slots_owned: player.property_slots[property_id],
+            available_slots: property.available_slots,
+            player_state: player_snapshot(player)?,
+        });
+
+        Ok(())
+    }
+
+    // ========== STAKING ==========
+
+    /// Locks game tokens for `lock_days` in exchange for a boost on income
+    /// accrual. One position per player; pending rewards are settled at the
+    /// old rate before the boost applies.
+    /// Staking again adds to the existing position and re-locks all of it
+    /// for `lock_days`, which may not end before the current lock does
+    pub fn stake_tokens(
+        ctx: Context<StakeTokens>,
+        amount: u64,
+        lock_days: u16,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let clock = Clock::get()?;
+
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(game_config.stake_boost_unit > 0, ErrorCode::StakingDisabled);
+        require!(amount > 0, ErrorCode::InvalidStakeAmount);
+        require!(
+            lock_days > 0 && lock_days <= game_config.stake_max_lock_days,
+            ErrorCode::InvalidLockDuration
+        );
+
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+        let vault_balance_before = token_balance(&ctx.accounts.stake_vault.to_account_info())?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.player_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.player.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        // Positions record what the vault received, net of any transfer fee
+        let amount = token_balance(&ctx.accounts.stake_vault.to_account_info())?
+            .checked_sub(vault_balance_before)
+            .ok_or(ErrorCode::Overflow)?;
+        let unlock_at = clock.unix_timestamp
+            .checked_add((lock_days as i64).checked_mul(86400).ok_or(ErrorCode::Overflow)?)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let stake_position = &mut ctx.accounts.stake_position;
+        if stake_position.owner == Pubkey::default() {
+            stake_position.owner = player.owner;
+            stake_position.bump = ctx.bumps.stake_position;
+        } else {
+            require!(unlock_at >= stake_position.unlock_at, ErrorCode::InvalidLockDuration);
+        }
+        let total_amount = stake_position.amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let boost_bps = calculate_stake_boost(game_config, total_amount, lock_days)?;
+
+        stake_position.amount = total_amount;
+        stake_position.boost_bps = boost_bps;
+        stake_position.lock_days = lock_days;
+        stake_position.staked_at = clock.unix_timestamp;
+        stake_position.unlock_at = unlock_at;
+
+        player.stake_boost_bps = boost_bps;
+        player.stake_unlock_at = unlock_at;
+
+        emit!(TokensStakedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            amount,
+            lock_days,
+            boost_bps,
+            unlock_at,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the staked tokens and closes the position. Unlocking before
+    /// `unlock_at` forfeits `stake_early_unlock_penalty_bps` to the reward pool.
+    pub fn unstake_tokens(ctx: Context<UnstakeTokens>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let stake_position = &ctx.accounts.stake_position;
+        let clock = Clock::get()?;
+
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+        let early = clock.unix_timestamp < stake_position.unlock_at;
+        let penalty = if early {
+            stake_position.amount
+                .checked_mul(game_config.stake_early_unlock_penalty_bps as u64)
+                .ok_or(ErrorCode::Overflow)?
+                .checked_div(10000)
+                .ok_or(ErrorCode::Overflow)?
+        } else {
+            0
+        };
+        let returned = stake_position.amount
+            .checked_sub(penalty)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let game_config_key = ctx.accounts.game_config.key();
+        let seeds = &[
+            b"stake_vault",
+            game_config_key.as_ref(),
+            &[ctx.bumps.stake_vault],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        if returned > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.stake_vault.to_account_info(),
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: ctx.accounts.player_token_account.to_account_info(),
+                        authority: ctx.accounts.stake_vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                returned,
+                ctx.accounts.token_mint.decimals,
+            )?;
+        }
+        if penalty > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.stake_vault.to_account_info(),
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: ctx.accounts.reward_pool_vault.to_account_info(),
+                        authority: ctx.accounts.stake_vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                penalty,
+                ctx.accounts.token_mint.decimals,
+            )?;
+        }
+
+        player.stake_boost_bps = 0;
+        player.stake_unlock_at = 0;
+
+        emit!(TokensUnstakedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            amount: stake_position.amount,
+            penalty,
+            early,
+        });
+
+        Ok(())
+    }
+
+    /// Brings any player account up to date without the owner: settles
+    /// rewards at the current property rates, clears expired shields,
+    /// protections, insurance and retaliation windows, and recomputes set
+    /// completion. Pays the cranker `crank_tip_amount` out of the tip budget
+    /// when the account hadn't been settled for `crank_min_interval_seconds`.
+    pub fn crank_player(ctx: Context<CrankPlayer>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+
+        let stale_for = now.saturating_sub(player.last_accumulation_timestamp);
+
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+        let expired = expire_player_state(player, now);
+        for set_id in 0..MAX_SETS {
+            refresh_set_completion(player, set_id, now)?;
+        }
+
+        // Empty accounts are free to create, so they never earn a tip
+        let tip = game_config.crank_tip_amount;
+        let pays_tip = tip > 0
+            && player.total_slots_owned > 0
+            && stale_for >= game_config.crank_min_interval_seconds
+            && game_config.crank_tip_budget >= tip
+            && available_reward_pool(&ctx.accounts.reward_pool_vault, game_config) >= tip;
+
+        if pays_tip {
+            game_config.crank_tip_budget = game_config.crank_tip_budget
+                .checked_sub(tip)
+                .ok_or(ErrorCode::Overflow)?;
+
+            let game_config_key = ctx.accounts.game_config.key();
+            let seeds = &[
+                b"reward_pool_vault",
+                game_config_key.as_ref(),
+                &[game_config.reward_pool_vault_bump],
+            ];
+            let signer_seeds = &[&seeds[..]];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.reward_pool_vault.to_account_info(),
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: ctx.accounts.cranker_token_account.to_account_info(),
+                        authority: ctx.accounts.reward_pool_vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                tip,
+                ctx.accounts.token_mint.decimals,
+            )?;
+        }
+
+        emit!(PlayerCrankedEvent {
+            header: next_event_header(game_config)?,
+            player: player.owner,
+            cranker: ctx.accounts.cranker.key(),
+            tip: if pays_tip { tip } else { 0 },
+            expired,
+            player_state: player_snapshot(player)?,
         });
 
         Ok(())
@@ -781,10 +1719,14 @@ pub mod defipoly_program {
         property_id: u8,
         new_price: u64,
     ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         ctx.accounts.property.price = new_price;
+        checkpoint_property_income(game_config, &ctx.accounts.property, Clock::get()?.unix_timestamp)?;
+
         emit!(AdminUpdateEvent {
+            header: next_event_header(game_config)?,
             property_id,
-            update_type: "price".to_string(),
+            update_type: AdminUpdateType::Price,
             new_value: new_price,
         });
         Ok(())
@@ -804,9 +1746,22 @@ pub mod defipoly_program {
             .checked_add(slots_difference)
             .ok_or(ErrorCode::Overflow)?;
         property.available_slots = new_available as u16;
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let set_id = property.set_id as usize;
+        game_config.set_total_supply[set_id] = (game_config.set_total_supply[set_id] as i64)
+            .checked_add(slots_difference as i64)
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(ErrorCode::Overflow)?;
+        game_config.total_supply = (game_config.total_supply as i64)
+            .checked_add(slots_difference as i64)
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(ErrorCode::Overflow)?;
+
         emit!(AdminUpdateEvent {
+            header: next_event_header(game_config)?,
             property_id,
-            update_type: "max_slots".to_string(),
+            update_type: AdminUpdateType::MaxSlots,
             new_value: new_max_slots as u64,
         });
         Ok(())
@@ -818,55 +1773,591 @@ pub mod defipoly_program {
     ) -> Result<()> {
         let game_config = &mut ctx.accounts.game_config.load_mut()?;
         game_config.steal_chance_bps = chance_bps;
+
+        emit!(StealChanceUpdatedEvent {
+            header: next_event_header(game_config)?,
+            steal_chance_bps: chance_bps,
+        });
+
         Ok(())
     }
 
-    pub fn pause_game(ctx: Context<AdminUpdateGame>) -> Result<()> {
+    pub fn admin_update_steal_mode(
+        ctx: Context<AdminUpdateGame>,
+        steal_mode: u8,
+        max_steal_slots: u16,
+    ) -> Result<()> {
+        require!(
+            steal_mode == STEAL_MODE_SINGLE || steal_mode == STEAL_MODE_MULTI,
+            ErrorCode::InvalidStealMode
+        );
+        require!(
+            (1..=MAX_STEAL_SLOTS_PER_ATTEMPT).contains(&max_steal_slots),
+            ErrorCode::InvalidStealSlots
+        );
+
         let game_config = &mut ctx.accounts.game_config.load_mut()?;
-        game_config.game_paused = 1;
+        game_config.steal_mode = steal_mode;
+        game_config.max_steal_slots = max_steal_slots;
+
+        emit!(StealModeUpdatedEvent {
+            header: next_event_header(game_config)?,
+            steal_mode,
+            max_steal_slots,
+        });
+
         Ok(())
     }
 
-    pub fn unpause_game(ctx: Context<AdminUpdateGame>) -> Result<()> {
+    pub fn admin_update_defense_params(
+        ctx: Context<AdminUpdateGame>,
+        retaliation_window_seconds: i64,
+        retaliation_cost_bps: u16,
+        insurance_premium_bps: u16,
+        insurance_coverage_bps: u16,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_RETALIATION_WINDOW_SECONDS).contains(&retaliation_window_seconds),
+            ErrorCode::InvalidRetaliationWindow
+        );
+        require!(retaliation_cost_bps <= 10000, ErrorCode::InvalidStealCost);
+        require!(insurance_premium_bps <= 10000, ErrorCode::InvalidInsuranceRate);
+        require!(insurance_coverage_bps <= 10000, ErrorCode::InvalidInsuranceRate);
+
         let game_config = &mut ctx.accounts.game_config.load_mut()?;
-        game_config.game_paused = 0;
-        Ok(())
-    }
+        game_config.retaliation_window_seconds = retaliation_window_seconds;
+        game_config.retaliation_cost_bps = retaliation_cost_bps;
+        game_config.insurance_premium_bps = insurance_premium_bps;
+        game_config.insurance_coverage_bps = insurance_coverage_bps;
+
+        emit!(DefenseParamsUpdatedEvent {
+            header: next_event_header(game_config)?,
+            retaliation_window_seconds,
+            retaliation_cost_bps,
+            insurance_premium_bps,
+            insurance_coverage_bps,
+        });
 
-    pub fn close_player_account(ctx: Context<ClosePlayerAccount>) -> Result<()> {
-        let player = ctx.accounts.player_account.load()?;
-        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
         Ok(())
     }
 
-    pub fn admin_close_player_account(ctx: Context<AdminClosePlayerAccount>) -> Result<()> {
+    pub fn admin_update_steal_protection(
+        ctx: Context<AdminUpdateGame>,
+        protection_success_seconds: i64,
+        protection_failed_seconds: i64,
+        steal_cooldown_bps: u16,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.player_account.owner == ctx.program_id,
-            ErrorCode::Unauthorized
+            (0..=MAX_STEAL_PROTECTION_SECONDS).contains(&protection_success_seconds),
+            ErrorCode::InvalidStealProtection
         );
-        
-        let dest_starting_lamports = ctx.accounts.rent_receiver.lamports();
-        let account_lamports = ctx.accounts.player_account.lamports();
-        
-        **ctx.accounts.rent_receiver.lamports.borrow_mut() = dest_starting_lamports
-            .checked_add(account_lamports)
-            .ok_or(ErrorCode::Overflow)?;
-        **ctx.accounts.player_account.lamports.borrow_mut() = 0;
-        
-        let mut data = ctx.accounts.player_account.try_borrow_mut_data()?;
-        data.fill(0);
-        
+        require!(
+            (0..=MAX_STEAL_PROTECTION_SECONDS).contains(&protection_failed_seconds),
+            ErrorCode::InvalidStealProtection
+        );
+        require!(steal_cooldown_bps <= 10000, ErrorCode::InvalidCooldown);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.steal_protection_success_seconds = protection_success_seconds;
+        game_config.steal_protection_failed_seconds = protection_failed_seconds;
+        game_config.steal_cooldown_bps = steal_cooldown_bps;
+
+        emit!(StealProtectionUpdatedEvent {
+            header: next_event_header(game_config)?,
+            protection_success_seconds,
+            protection_failed_seconds,
+            steal_cooldown_bps,
+        });
+
         Ok(())
     }
 
-    pub fn admin_grant_property(
-        ctx: Context<AdminGrantProperty>,
-        _target_player: Pubkey,
-        slots: u16,
+    pub fn admin_update_purchase_limits(
+        ctx: Context<AdminUpdateGame>,
+        purchase_limit_flags: u8,
+        property_purchase_cooldown_seconds: i64,
+        purchase_window_seconds: i64,
+        purchase_window_max_slots: u16,
     ) -> Result<()> {
-        let game_config = &ctx.accounts.game_config.load()?;
-        let property = &mut ctx.accounts.property;
-        let player = &mut ctx.accounts.player_account.load_mut()?;
+        require!(
+            purchase_limit_flags & !PURCHASE_LIMIT_ALL == 0,
+            ErrorCode::InvalidPurchaseLimits
+        );
+        require!(
+            (0..=MAX_PURCHASE_COOLDOWN_SECONDS).contains(&property_purchase_cooldown_seconds),
+            ErrorCode::InvalidCooldown
+        );
+        if purchase_limit_flags & PURCHASE_LIMIT_GLOBAL != 0 {
+            require!(
+                purchase_window_seconds > 0 && purchase_window_max_slots > 0,
+                ErrorCode::InvalidPurchaseLimits
+            );
+        }
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.purchase_limit_flags = purchase_limit_flags;
+        game_config.property_purchase_cooldown_seconds = property_purchase_cooldown_seconds;
+        game_config.purchase_window_seconds = purchase_window_seconds;
+        game_config.purchase_window_max_slots = purchase_window_max_slots;
+
+        emit!(PurchaseLimitsUpdatedEvent {
+            header: next_event_header(game_config)?,
+            purchase_limit_flags,
+            property_purchase_cooldown_seconds,
+            purchase_window_seconds,
+            purchase_window_max_slots,
+        });
+
+        Ok(())
+    }
+
+    /// Caps on a player's share of a set's total supply and of the total
+    /// supply across all properties. 0 disables a cap.
+    pub fn admin_update_concentration_caps(
+        ctx: Context<AdminUpdateGame>,
+        max_set_share_bps: u16,
+        max_global_share_bps: u16,
+    ) -> Result<()> {
+        require!(max_set_share_bps <= 10000, ErrorCode::InvalidConcentrationCap);
+        require!(max_global_share_bps <= 10000, ErrorCode::InvalidConcentrationCap);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.max_set_share_bps = max_set_share_bps;
+        game_config.max_global_share_bps = max_global_share_bps;
+
+        emit!(ConcentrationCapsUpdatedEvent {
+            header: next_event_header(game_config)?,
+            max_set_share_bps,
+            max_global_share_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the early-access allowlist. Leaves are sha256(wallet || allocation_le);
+    /// while `now < early_access_end` and the root is non-zero, players must
+    /// present a proof to register and to buy, up to their allocation.
+    pub fn admin_update_allowlist(
+        ctx: Context<AdminUpdateGame>,
+        allowlist_root: [u8; 32],
+        early_access_end: i64,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.allowlist_root = allowlist_root;
+        game_config.early_access_end = early_access_end;
+
+        emit!(AllowlistUpdatedEvent {
+            header: next_event_header(game_config)?,
+            allowlist_root,
+            early_access_end,
+        });
+
+        Ok(())
+    }
+
+    /// Sell refund ramps linearly from `sell_base_bps` to `sell_max_bps`
+    /// over `sell_ramp_days` of holding.
+    pub fn admin_update_sell_curve(
+        ctx: Context<AdminUpdateGame>,
+        sell_base_bps: u16,
+        sell_max_bps: u16,
+        sell_ramp_days: u16,
+    ) -> Result<()> {
+        validate_sell_curve(sell_base_bps, sell_max_bps, sell_ramp_days)?;
+        require!(sell_max_bps > 0, ErrorCode::InvalidSellCurve);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.sell_base_bps = sell_base_bps;
+        game_config.sell_max_bps = sell_max_bps;
+        game_config.sell_ramp_days = sell_ramp_days;
+
+        emit!(SellCurveUpdatedEvent {
+            header: next_event_header(game_config)?,
+            sell_base_bps,
+            sell_max_bps,
+            sell_ramp_days,
+        });
+
+        Ok(())
+    }
+
+    /// Boost = whole `stake_boost_unit`s staked x lock days x
+    /// `stake_boost_bps_per_unit_day`, capped at `stake_max_boost_bps`.
+    /// A zero unit disables new stakes.
+    pub fn admin_update_staking_params(
+        ctx: Context<AdminUpdateGame>,
+        stake_boost_unit: u64,
+        stake_boost_bps_per_unit_day: u16,
+        stake_max_boost_bps: u16,
+        stake_early_unlock_penalty_bps: u16,
+        stake_max_lock_days: u16,
+    ) -> Result<()> {
+        require!(stake_max_boost_bps <= 10000, ErrorCode::InvalidStakingParams);
+        require!(stake_early_unlock_penalty_bps <= 10000, ErrorCode::InvalidStakingParams);
+        require!(stake_max_lock_days <= 365, ErrorCode::InvalidStakingParams);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.stake_boost_unit = stake_boost_unit;
+        game_config.stake_boost_bps_per_unit_day = stake_boost_bps_per_unit_day;
+        game_config.stake_max_boost_bps = stake_max_boost_bps;
+        game_config.stake_early_unlock_penalty_bps = stake_early_unlock_penalty_bps;
+        game_config.stake_max_lock_days = stake_max_lock_days;
+
+        emit!(StakingParamsUpdatedEvent {
+            header: next_event_header(game_config)?,
+            stake_boost_unit,
+            stake_boost_bps_per_unit_day,
+            stake_max_boost_bps,
+            stake_early_unlock_penalty_bps,
+            stake_max_lock_days,
+        });
+
+        Ok(())
+    }
+
+    /// A zero period disables vested claims; existing schedules keep vesting.
+    pub fn admin_update_vesting_params(
+        ctx: Context<AdminUpdateGame>,
+        vesting_period_seconds: i64,
+        vesting_bonus_bps: u16,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_VESTING_PERIOD_SECONDS).contains(&vesting_period_seconds),
+            ErrorCode::InvalidVestingParams
+        );
+        require!(vesting_bonus_bps <= 5000, ErrorCode::InvalidVestingParams);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.vesting_period_seconds = vesting_period_seconds;
+        game_config.vesting_bonus_bps = vesting_bonus_bps;
+
+        emit!(VestingParamsUpdatedEvent {
+            header: next_event_header(game_config)?,
+            vesting_period_seconds,
+            vesting_bonus_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_update_burn_rate(
+        ctx: Context<AdminUpdateGame>,
+        burn_bps: u16,
+    ) -> Result<()> {
+        require!(burn_bps <= MAX_BURN_BPS, ErrorCode::InvalidBurnRate);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.burn_bps = burn_bps;
+
+        emit!(BurnRateUpdatedEvent {
+            header: next_event_header(game_config)?,
+            burn_bps,
+        });
+
+        Ok(())
+    }
+
+    /// `tip_budget` replaces the remaining budget; tips stop once it runs out
+    pub fn admin_update_crank_params(
+        ctx: Context<AdminUpdateGame>,
+        tip_amount: u64,
+        min_interval_seconds: i64,
+        tip_budget: u64,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_CRANK_INTERVAL_SECONDS).contains(&min_interval_seconds),
+            ErrorCode::InvalidCrankParams
+        );
+        // Without an interval the same account could be cranked for a tip
+        // in every transaction
+        require!(tip_amount == 0 || min_interval_seconds > 0, ErrorCode::InvalidCrankParams);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.crank_tip_amount = tip_amount;
+        game_config.crank_min_interval_seconds = min_interval_seconds;
+        game_config.crank_tip_budget = tip_budget;
+
+        emit!(CrankParamsUpdatedEvent {
+            header: next_event_header(game_config)?,
+            tip_amount,
+            min_interval_seconds,
+            tip_budget,
+        });
+
+        Ok(())
+    }
+
+    /// Whitelists an alternate payment mint and creates its proceeds vault
+    pub fn admin_add_payment_mint(
+        ctx: Context<AddPaymentMint>,
+        max_staleness_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.payment_token_mint.key() != ctx.accounts.game_config.load()?.token_mint,
+            ErrorCode::InvalidPaymentMint
+        );
+        require!(max_staleness_seconds > 0, ErrorCode::InvalidPaymentMint);
+
+        let payment_mint = &mut ctx.accounts.payment_mint_config;
+        payment_mint.mint = ctx.accounts.payment_token_mint.key();
+        payment_mint.vault = ctx.accounts.payment_vault.key();
+        payment_mint.price = 0;
+        payment_mint.expo = 0;
+        payment_mint.publish_time = 0;
+        payment_mint.max_staleness_seconds = max_staleness_seconds;
+        payment_mint.total_received = 0;
+        payment_mint.reward_pool_owed = 0;
+        payment_mint.enabled = 1;
+        payment_mint.bump = ctx.bumps.payment_mint_config;
+        payment_mint.vault_bump = ctx.bumps.payment_vault;
+
+        emit!(PaymentMintUpdatedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            mint: payment_mint.mint,
+            enabled: true,
+            max_staleness_seconds,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_update_payment_mint(
+        ctx: Context<UpdatePaymentMint>,
+        enabled: bool,
+        max_staleness_seconds: i64,
+    ) -> Result<()> {
+        require!(max_staleness_seconds > 0, ErrorCode::InvalidPaymentMint);
+
+        let payment_mint = &mut ctx.accounts.payment_mint_config;
+        payment_mint.enabled = enabled as u8;
+        payment_mint.max_staleness_seconds = max_staleness_seconds;
+
+        emit!(PaymentMintUpdatedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            mint: payment_mint.mint,
+            enabled,
+            max_staleness_seconds,
+        });
+
+        Ok(())
+    }
+
+    /// Posts the price of one whole game token in whole payment tokens as
+    /// `price * 10^expo`, oracle style, stamped with the current time.
+    pub fn admin_post_payment_rate(
+        ctx: Context<PostPaymentRate>,
+        price: u64,
+        expo: i32,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidPaymentRate);
+        require!((-18..=18).contains(&expo), ErrorCode::InvalidPaymentRate);
+
+        let clock = Clock::get()?;
+        let payment_mint = &mut ctx.accounts.payment_mint_config;
+        payment_mint.price = price;
+        payment_mint.expo = expo;
+        payment_mint.publish_time = clock.unix_timestamp;
+
+        emit!(PaymentRatePostedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            mint: payment_mint.mint,
+            price,
+            expo,
+            publish_time: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_withdraw_payment_vault(
+        ctx: Context<WithdrawPaymentVault>,
+        amount: u64,
+    ) -> Result<()> {
+        let payment_mint = &ctx.accounts.payment_mint_config;
+        require!(payment_mint.reward_pool_owed == 0, ErrorCode::RewardPoolOwed);
+        let seeds = &[
+            b"payment_vault",
+            payment_mint.mint.as_ref(),
+            &[payment_mint.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.payment_vault.to_account_info(),
+                mint: ctx.accounts.payment_token_mint.to_account_info(),
+                to: ctx.accounts.destination_account.to_account_info(),
+                authority: ctx.accounts.payment_vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.payment_token_mint.decimals)?;
+
+        emit!(AdminWithdrawEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            admin: ctx.accounts.authority.key(),
+            amount,
+            destination: ctx.accounts.destination_account.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Pays game tokens into the reward pool against what a payment mint's
+    /// purchases owe it. Credits what the pool actually received.
+    pub fn admin_settle_payment_liability(
+        ctx: Context<SettlePaymentLiability>,
+        amount: u64,
+    ) -> Result<()> {
+        let payment_mint = &mut ctx.accounts.payment_mint_config;
+        require!(amount > 0 && amount <= payment_mint.reward_pool_owed, ErrorCode::InvalidSettlementAmount);
+
+        let pool_balance_before = token_balance(&ctx.accounts.reward_pool_vault.to_account_info())?;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.authority_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    to: ctx.accounts.reward_pool_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
+        let received = token_balance(&ctx.accounts.reward_pool_vault.to_account_info())?
+            .checked_sub(pool_balance_before)
+            .ok_or(ErrorCode::Overflow)?;
+
+        payment_mint.reward_pool_owed = payment_mint.reward_pool_owed
+            .checked_sub(received)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(PaymentLiabilitySettledEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            mint: payment_mint.mint,
+            amount: received,
+            remaining_owed: payment_mint.reward_pool_owed,
+        });
+
+        Ok(())
+    }
+
+    /// Reports the reward pool balance followed by the balance of each
+    /// payment vault passed in `remaining_accounts`.
+    pub fn report_reward_pool_balances<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReportRewardPoolBalances<'info>>,
+    ) -> Result<Vec<MintBalance>> {
+        let mut balances = vec![MintBalance {
+            mint: ctx.accounts.reward_pool_vault.mint,
+            balance: ctx.accounts.reward_pool_vault.amount,
+        }];
+
+        for vault_info in ctx.remaining_accounts.iter() {
+            let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"payment_vault", vault.mint.as_ref()],
+                ctx.program_id,
+            );
+            require!(vault_info.key() == expected, ErrorCode::InvalidPaymentMint);
+            balances.push(MintBalance {
+                mint: vault.mint,
+                balance: vault.amount,
+            });
+        }
+
+        Ok(balances)
+    }
+
+    pub fn pause_game(ctx: Context<AdminUpdateGame>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.game_paused = 1;
+
+        emit!(GamePauseEvent {
+            header: next_event_header(game_config)?,
+            paused: true,
+        });
+
+        Ok(())
+    }
+
+    pub fn unpause_game(ctx: Context<AdminUpdateGame>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.game_paused = 0;
+
+        emit!(GamePauseEvent {
+            header: next_event_header(game_config)?,
+            paused: false,
+        });
+
+        Ok(())
+    }
+
+    /// Closing forfeits nothing: rewards, referral rebates and insurance
+    /// payouts must be claimed first. Allowlist usage lives on the player
+    /// account, so closing is blocked during early access to keep a wallet
+    /// from re-initializing and reusing its allocation.
+    pub fn close_player_account(ctx: Context<ClosePlayerAccount>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let player = ctx.accounts.player_account.load()?;
+        require!(player.owner == ctx.accounts.player.key(), ErrorCode::Unauthorized);
+        require!(
+            !is_early_access_active(game_config, Clock::get()?.unix_timestamp),
+            ErrorCode::EarlyAccessActive
+        );
+        require!(
+            player.pending_rewards == 0
+                && player.pending_bonus_rewards == 0
+                && player.referral_rewards_pending == 0
+                && player.insurance_payout_pending == 0,
+            ErrorCode::UnclaimedBalances
+        );
+
+        emit!(PlayerAccountClosedEvent {
+            header: next_event_header(game_config)?,
+            player_account: ctx.accounts.player_account.key(),
+            closed_by: player.owner,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_close_player_account(ctx: Context<AdminClosePlayerAccount>) -> Result<()> {
+        require!(
+            ctx.accounts.player_account.owner == ctx.program_id,
+            ErrorCode::Unauthorized
+        );
+        
+        let dest_starting_lamports = ctx.accounts.rent_receiver.lamports();
+        let account_lamports = ctx.accounts.player_account.lamports();
+        
+        **ctx.accounts.rent_receiver.lamports.borrow_mut() = dest_starting_lamports
+            .checked_add(account_lamports)
+            .ok_or(ErrorCode::Overflow)?;
+        **ctx.accounts.player_account.lamports.borrow_mut() = 0;
+        
+        let mut data = ctx.accounts.player_account.try_borrow_mut_data()?;
+        data.fill(0);
+
+        emit!(PlayerAccountClosedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            player_account: ctx.accounts.player_account.key(),
+            closed_by: ctx.accounts.authority.key(),
+        });
+        
+        Ok(())
+    }
+
+    pub fn admin_grant_property(
+        ctx: Context<AdminGrantProperty>,
+        _target_player: Pubkey,
+        slots: u16,
+    ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let property = &mut ctx.accounts.property;
+        let player = &mut ctx.accounts.player_account.load_mut()?;
         let clock = Clock::get()?;
         
         let property_id = property.property_id as usize;
@@ -878,20 +2369,23 @@ pub mod defipoly_program {
             player.property_slots[property_id].checked_add(slots).ok_or(ErrorCode::Overflow)? <= property.max_per_player,
             ErrorCode::MaxSlotsReached
         );
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+        let set_id = property.set_id as usize;
+        check_concentration(player, set_id, get_concentration_caps(game_config, set_id), slots)?;
 
         property.available_slots = property.available_slots
             .checked_sub(slots)
             .ok_or(ErrorCode::Overflow)?;
 
+        record_acquisition(player, property_id, slots, clock.unix_timestamp)?;
         if player.property_slots[property_id] == 0 {
-            player.property_purchase_timestamp[property_id] = clock.unix_timestamp;
             player.properties_owned_count = player.properties_owned_count
                 .checked_add(1)
                 .ok_or(ErrorCode::Overflow)?;
             
-            let set_id = property.set_id as usize;
             let property_bit = get_property_bit_in_set(property.property_id, property.set_id);
             player.set_properties_mask[set_id] |= 1 << property_bit;
+            refresh_set_completion(player, set_id, clock.unix_timestamp)?;
         }
         
         player.property_slots[property_id] = player.property_slots[property_id]
@@ -902,24 +2396,17 @@ pub mod defipoly_program {
             .checked_add(slots)
             .ok_or(ErrorCode::Overflow)?;
 
-        let daily_income_per_slot = property.price
-            .checked_mul(property.yield_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)?;
-        let total_daily_income_increase = daily_income_per_slot
-            .checked_mul(slots as u64)
-            .ok_or(ErrorCode::Overflow)?;
-
-        player.total_base_daily_income = player.total_base_daily_income
-            .checked_add(total_daily_income_increase)
-            .ok_or(ErrorCode::Overflow)?;
+        player.total_base_daily_income = current_daily_income(player, game_config)?;
 
         emit!(AdminGrantEvent {
+            header: next_event_header(game_config)?,
             admin: ctx.accounts.authority.key(),
             target_player: player.owner,
             property_id: property.property_id,
             slots,
+            slots_owned: player.property_slots[property_id],
+            available_slots: property.available_slots,
+            player_state: player_snapshot(player)?,
         });
 
         Ok(())
@@ -929,13 +2416,17 @@ pub mod defipoly_program {
         ctx: Context<AdminRevokeProperty>,
         slots: u16,
     ) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         let property = &mut ctx.accounts.property;
         let player = &mut ctx.accounts.player_account.load_mut()?;
+        let clock = Clock::get()?;
         
         let property_id = property.property_id as usize;
 
         require!(player.property_slots[property_id] >= slots, ErrorCode::InsufficientSlots);
 
+        update_pending_rewards(player, game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
         player.property_slots[property_id] = player.property_slots[property_id]
             .checked_sub(slots)
             .ok_or(ErrorCode::Overflow)?;
@@ -943,6 +2434,9 @@ pub mod defipoly_program {
         if player.property_shielded[property_id] > player.property_slots[property_id] {
             player.property_shielded[property_id] = player.property_slots[property_id];
         }
+        if player.property_insured_slots[property_id] > player.property_slots[property_id] {
+            player.property_insured_slots[property_id] = player.property_slots[property_id];
+        }
 
         if player.property_slots[property_id] == 0 {
             let set_id = property.set_id as usize;
@@ -951,6 +2445,7 @@ pub mod defipoly_program {
             player.properties_owned_count = player.properties_owned_count
                 .checked_sub(1)
                 .ok_or(ErrorCode::Overflow)?;
+            refresh_set_completion(player, set_id, clock.unix_timestamp)?;
         }
 
         property.available_slots = property.available_slots
@@ -961,24 +2456,17 @@ pub mod defipoly_program {
             .checked_sub(slots)
             .ok_or(ErrorCode::Overflow)?;
 
-        let daily_income_per_slot = property.price
-            .checked_mul(property.yield_percent_bps as u64)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::Overflow)?;
-        let total_daily_income_decrease = daily_income_per_slot
-            .checked_mul(slots as u64)
-            .ok_or(ErrorCode::Overflow)?;
-
-        player.total_base_daily_income = player.total_base_daily_income
-            .checked_sub(total_daily_income_decrease)
-            .ok_or(ErrorCode::Overflow)?;
+        player.total_base_daily_income = current_daily_income(player, game_config)?;
 
         emit!(AdminRevokeEvent {
+            header: next_event_header(game_config)?,
             admin: ctx.accounts.authority.key(),
             target_player: player.owner,
             property_id: property.property_id,
             slots,
+            slots_owned: player.property_slots[property_id],
+            available_slots: property.available_slots,
+            player_state: player_snapshot(player)?,
         });
 
         Ok(())
@@ -991,11 +2479,14 @@ pub mod defipoly_program {
     ) -> Result<()> {
         require!(new_yield_bps <= 10000, ErrorCode::InvalidYield);
         
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         ctx.accounts.property.yield_percent_bps = new_yield_bps;
+        checkpoint_property_income(game_config, &ctx.accounts.property, Clock::get()?.unix_timestamp)?;
         
         emit!(AdminUpdateEvent {
+            header: next_event_header(game_config)?,
             property_id,
-            update_type: "yield".to_string(),
+            update_type: AdminUpdateType::Yield,
             new_value: new_yield_bps as u64,
         });
         
@@ -1012,8 +2503,9 @@ pub mod defipoly_program {
         ctx.accounts.property.shield_cost_percent_bps = new_shield_cost_bps;
         
         emit!(AdminUpdateEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
             property_id,
-            update_type: "shield_cost".to_string(),
+            update_type: AdminUpdateType::ShieldCost,
             new_value: new_shield_cost_bps as u64,
         });
         
@@ -1030,34 +2522,215 @@ pub mod defipoly_program {
         ctx.accounts.property.cooldown_seconds = new_cooldown_seconds;
         
         emit!(AdminUpdateEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
             property_id,
-            update_type: "cooldown".to_string(),
+            update_type: AdminUpdateType::Cooldown,
             new_value: new_cooldown_seconds as u64,
         });
         
         Ok(())
     }
 
-    pub fn admin_update_set_bonus(
-        ctx: Context<AdminUpdateGame>,
-        set_id: u8,
-        bonus_bps: u16,
+    /// Per-property overrides for steal protection and steal cooldown.
+    /// A value of 0 falls back to the global `GameConfig` setting.
+    pub fn admin_update_property_steal_params(
+        ctx: Context<AdminUpdateProperty>,
+        property_id: u8,
+        protection_success_seconds: i64,
+        protection_failed_seconds: i64,
+        steal_cooldown_seconds: i64,
     ) -> Result<()> {
-        require!(set_id < 8, ErrorCode::InvalidSetId);
-        require!(bonus_bps <= 10000, ErrorCode::InvalidBonus);
-        
-        let game_config = &mut ctx.accounts.game_config.load_mut()?;
-        game_config.set_bonus_bps[set_id as usize] = bonus_bps;
-        
-        Ok(())
-    }
+        require!(
+            (0..=MAX_STEAL_PROTECTION_SECONDS).contains(&protection_success_seconds),
+            ErrorCode::InvalidStealProtection
+        );
+        require!(
+            (0..=MAX_STEAL_PROTECTION_SECONDS).contains(&protection_failed_seconds),
+            ErrorCode::InvalidStealProtection
+        );
+        require!(
+            (0..=MAX_STEAL_COOLDOWN_SECONDS).contains(&steal_cooldown_seconds),
+            ErrorCode::InvalidCooldown
+        );
 
-    pub fn admin_clear_cooldown(
-        ctx: Context<AdminClearCooldown>,
-        set_id: u8,
+        let property = &mut ctx.accounts.property;
+        property.steal_protection_success_seconds = protection_success_seconds;
+        property.steal_protection_failed_seconds = protection_failed_seconds;
+        property.steal_cooldown_seconds = steal_cooldown_seconds;
+
+        emit!(PropertyStealParamsUpdatedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            property_id,
+            protection_success_seconds,
+            protection_failed_seconds,
+            steal_cooldown_seconds,
+        });
+
+        Ok(())
+    }
+
+    /// Per-property sell curve; a zero `sell_max_bps` falls back to the
+    /// global curve.
+    pub fn admin_update_property_sell_curve(
+        ctx: Context<AdminUpdateProperty>,
+        property_id: u8,
+        sell_base_bps: u16,
+        sell_max_bps: u16,
+        sell_ramp_days: u16,
+    ) -> Result<()> {
+        validate_sell_curve(sell_base_bps, sell_max_bps, sell_ramp_days)?;
+
+        let property = &mut ctx.accounts.property;
+        property.sell_base_bps = sell_base_bps;
+        property.sell_max_bps = sell_max_bps;
+        property.sell_ramp_days = sell_ramp_days;
+
+        emit!(PropertySellCurveUpdatedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            property_id,
+            sell_base_bps,
+            sell_max_bps,
+            sell_ramp_days,
+        });
+
+        Ok(())
+    }
+
+    /// Switches a property between flat and bonding-curve pricing. Yield and
+    /// shield cost keep using the base `price`, so income per slot is the
+    /// same for every holder wherever they entered the curve.
+    pub fn admin_update_property_pricing(
+        ctx: Context<AdminUpdateProperty>,
+        property_id: u8,
+        pricing_mode: u8,
+        curve_param: u64,
+    ) -> Result<()> {
+        match pricing_mode {
+            PRICING_FLAT => require!(curve_param == 0, ErrorCode::InvalidPricingCurve),
+            PRICING_LINEAR => {}
+            PRICING_EXPONENTIAL => require!(
+                curve_param > 0 && curve_param <= MAX_CURVE_GROWTH_BPS,
+                ErrorCode::InvalidPricingCurve
+            ),
+            _ => return err!(ErrorCode::InvalidPricingCurve),
+        }
+
+        let property = &mut ctx.accounts.property;
+        property.pricing_mode = pricing_mode;
+        property.curve_param = curve_param;
+
+        // Make sure the full supply can still be priced
+        get_curve_cost(property, 0, property.max_slots_per_property)?;
+
+        emit!(PropertyPricingUpdatedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            property_id,
+            pricing_mode,
+            curve_param,
+        });
+
+        Ok(())
+    }
+
+    /// Opens a freshly initialized property through a Dutch auction: the
+    /// price decays linearly from `start_price` to the property's `price`
+    /// over `duration_seconds`, with an optional per-wallet cap and an
+    /// optional Merkle allowlist (zero root = open to everyone).
+    pub fn admin_schedule_property_launch(
+        ctx: Context<AdminUpdateProperty>,
+        property_id: u8,
+        start_time: i64,
+        duration_seconds: i64,
+        start_price: u64,
+        wallet_cap: u16,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+
+        require!(get_slots_sold(property)? == 0, ErrorCode::PropertyAlreadyLaunched);
+        require!(property.launch_stage != LAUNCH_AUCTION, ErrorCode::PropertyAlreadyLaunched);
+        require!(
+            duration_seconds > 0 && duration_seconds <= MAX_LAUNCH_DURATION_SECONDS,
+            ErrorCode::InvalidLaunchConfig
+        );
+        require!(start_price >= property.price, ErrorCode::InvalidLaunchConfig);
+
+        property.launch_start_time = start_time;
+        property.launch_end_time = start_time
+            .checked_add(duration_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        property.launch_start_price = start_price;
+        property.launch_wallet_cap = wallet_cap;
+        property.launch_merkle_root = merkle_root;
+        property.launch_stage = LAUNCH_SCHEDULED;
+
+        emit!(PropertyLaunchEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            property_id,
+            stage: LAUNCH_SCHEDULED,
+            price: start_price,
+            timestamp: start_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn admin_update_set_bonus(
+        ctx: Context<AdminUpdateGame>,
+        set_id: u8,
+        bonus_bps: u16,
+    ) -> Result<()> {
+        require!(set_id < 8, ErrorCode::InvalidSetId);
+        require!(bonus_bps <= 10000, ErrorCode::InvalidBonus);
+        
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        game_config.set_bonus_bps[set_id as usize] = bonus_bps;
+
+        emit!(SetBonusUpdatedEvent {
+            header: next_event_header(game_config)?,
+            set_id,
+            bonus_bps,
+        });
+        
+        Ok(())
+    }
+
+    pub fn admin_clear_cooldown(
+        ctx: Context<AdminClearCooldown>,
+        scope: CooldownScope,
+        index: u8,
     ) -> Result<()> {
         let player = &mut ctx.accounts.player_account.load_mut()?;
-        player.set_cooldown_timestamp[set_id as usize] = 0;
+
+        match scope {
+            CooldownScope::Set => {
+                require!((index as usize) < MAX_SETS, ErrorCode::InvalidSetId);
+                player.set_cooldown_timestamp[index as usize] = 0;
+            }
+            CooldownScope::Property => {
+                require!((index as usize) < MAX_PROPERTIES, ErrorCode::InvalidPropertyId);
+                player.property_last_purchase_timestamp[index as usize] = 0;
+            }
+            CooldownScope::PurchaseWindow => {
+                player.purchase_window_start = 0;
+                player.purchase_window_slots = 0;
+                player.purchase_window_prev_slots = 0;
+            }
+            CooldownScope::All => {
+                player.set_cooldown_timestamp = [0i64; MAX_SETS];
+                player.property_last_purchase_timestamp = [0i64; MAX_PROPERTIES];
+                player.purchase_window_start = 0;
+                player.purchase_window_slots = 0;
+                player.purchase_window_prev_slots = 0;
+            }
+        }
+
+        emit!(CooldownClearedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            player: player.owner,
+            scope,
+            index,
+        });
         
         Ok(())
     }
@@ -1068,6 +2741,12 @@ pub mod defipoly_program {
     ) -> Result<()> {
         let player = &mut ctx.accounts.player_account.load_mut()?;
         player.steal_cooldown_timestamp[property_id as usize] = 0;
+
+        emit!(StealCooldownClearedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            player: player.owner,
+            property_id,
+        });
         
         Ok(())
     }
@@ -1082,7 +2761,7 @@ pub mod defipoly_program {
         let pid = property_id as usize;
         
         require!(
-            duration_hours >= 1 && duration_hours <= 168,
+            (1..=168).contains(&duration_hours),
             ErrorCode::InvalidShieldDuration
         );
         
@@ -1090,11 +2769,7 @@ pub mod defipoly_program {
             .checked_mul(3600)
             .ok_or(ErrorCode::Overflow)?;
         
-        let shield_start_time = if clock.unix_timestamp < player.property_steal_protection_expiry[pid] {
-            player.property_steal_protection_expiry[pid]
-        } else {
-            clock.unix_timestamp
-        };
+        let shield_start_time = get_shield_start(player, pid, clock.unix_timestamp);
         
         player.property_shielded[pid] = player.property_slots[pid];
         player.property_shield_expiry[pid] = shield_start_time
@@ -1105,6 +2780,7 @@ pub mod defipoly_program {
             .ok_or(ErrorCode::Overflow)?;
         
         emit!(AdminShieldGrantEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
             admin: ctx.accounts.authority.key(),
             player: player.owner,
             property_id,
@@ -1119,10 +2795,10 @@ pub mod defipoly_program {
         ctx: Context<AdminEmergencyWithdraw>,
         amount: u64,
     ) -> Result<()> {
-        let game_config = &ctx.accounts.game_config.load()?;
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
         
         require!(
-            ctx.accounts.reward_pool_vault.amount >= amount,
+            available_reward_pool(&ctx.accounts.reward_pool_vault, game_config) >= amount,
             ErrorCode::InsufficientRewardPool
         );
         
@@ -1136,16 +2812,18 @@ pub mod defipoly_program {
 
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.reward_pool_vault.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
                 to: ctx.accounts.destination_account.to_account_info(),
                 authority: ctx.accounts.reward_pool_vault.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, amount)?;
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.token_mint.decimals)?;
 
         emit!(AdminWithdrawEvent {
+            header: next_event_header(game_config)?,
             admin: ctx.accounts.authority.key(),
             amount,
             destination: ctx.accounts.destination_account.key(),
@@ -1164,6 +2842,7 @@ pub mod defipoly_program {
         game_config.authority = new_authority;
         
         emit!(AdminAuthorityTransferEvent {
+            header: next_event_header(game_config)?,
             old_authority,
             new_authority,
         });
@@ -1174,6 +2853,7 @@ pub mod defipoly_program {
     pub fn admin_update_global_rates(
         ctx: Context<AdminUpdateGame>,
         steal_cost_bps: Option<u16>,
+        referral_bps: Option<u16>,
     ) -> Result<()> {
         let game_config = &mut ctx.accounts.game_config.load_mut()?;
         
@@ -1181,304 +2861,2950 @@ pub mod defipoly_program {
             require!(cost <= 10000, ErrorCode::InvalidStealCost);
             game_config.steal_cost_percent_bps = cost;
         }
-        
+
+        if let Some(referral) = referral_bps {
+            require!(referral <= MAX_REFERRAL_BPS, ErrorCode::InvalidReferralRate);
+            game_config.referral_bps = referral;
+        }
+
+        emit!(GlobalRatesUpdatedEvent {
+            header: next_event_header(game_config)?,
+            steal_cost_percent_bps: game_config.steal_cost_percent_bps,
+            referral_bps: game_config.referral_bps,
+        });
         
         Ok(())
     }
 
-    pub fn admin_update_accumulation_bonus(
-        ctx: Context<AdminUpdateGame>,
-        tier1_threshold: u64,
-        tier1_bonus_bps: u16,
-        tier2_threshold: u64,
-        tier2_bonus_bps: u16,
-        tier3_threshold: u64,
-        tier3_bonus_bps: u16,
-        tier4_threshold: u64,
-        tier4_bonus_bps: u16,
-        tier5_threshold: u64,
-        tier5_bonus_bps: u16,
-        tier6_threshold: u64,
-        tier6_bonus_bps: u16,
-        tier7_threshold: u64,
-        tier7_bonus_bps: u16,
-        tier8_threshold: u64,
-        tier8_bonus_bps: u16,
-    ) -> Result<()> {
+    /// Creates the accumulation tier table, migrating any tiers still held
+    /// in the legacy `GameConfig` fields and clearing them.
+    pub fn initialize_accumulation_tiers(ctx: Context<InitializeAccumulationTiers>) -> Result<()> {
         let game_config = &mut ctx.accounts.game_config.load_mut()?;
-        
-        require!(tier1_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier2_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier3_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier4_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier5_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier6_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier7_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        require!(tier8_bonus_bps <= 5000, ErrorCode::InvalidBonus);
-        
-        game_config.accumulation_tier1_threshold = tier1_threshold;
-        game_config.accumulation_tier1_bonus_bps = tier1_bonus_bps;
-        game_config.accumulation_tier2_threshold = tier2_threshold;
-        game_config.accumulation_tier2_bonus_bps = tier2_bonus_bps;
-        game_config.accumulation_tier3_threshold = tier3_threshold;
-        game_config.accumulation_tier3_bonus_bps = tier3_bonus_bps;
-        game_config.accumulation_tier4_threshold = tier4_threshold;
-        game_config.accumulation_tier4_bonus_bps = tier4_bonus_bps;
-        game_config.accumulation_tier5_threshold = tier5_threshold;
-        game_config.accumulation_tier5_bonus_bps = tier5_bonus_bps;
-        game_config.accumulation_tier6_threshold = tier6_threshold;
-        game_config.accumulation_tier6_bonus_bps = tier6_bonus_bps;
-        game_config.accumulation_tier7_threshold = tier7_threshold;
-        game_config.accumulation_tier7_bonus_bps = tier7_bonus_bps;
-        game_config.accumulation_tier8_threshold = tier8_threshold;
-        game_config.accumulation_tier8_bonus_bps = tier8_bonus_bps;
-        
+
+        let mut entries: Vec<AccumulationTier> = [
+            (game_config.accumulation_tier1_threshold, game_config.accumulation_tier1_bonus_bps),
+            (game_config.accumulation_tier2_threshold, game_config.accumulation_tier2_bonus_bps),
+            (game_config.accumulation_tier3_threshold, game_config.accumulation_tier3_bonus_bps),
+            (game_config.accumulation_tier4_threshold, game_config.accumulation_tier4_bonus_bps),
+            (game_config.accumulation_tier5_threshold, game_config.accumulation_tier5_bonus_bps),
+            (game_config.accumulation_tier6_threshold, game_config.accumulation_tier6_bonus_bps),
+            (game_config.accumulation_tier7_threshold, game_config.accumulation_tier7_bonus_bps),
+            (game_config.accumulation_tier8_threshold, game_config.accumulation_tier8_bonus_bps),
+        ]
+        .iter()
+        .filter(|(threshold, _)| *threshold > 0)
+        .map(|&(threshold, bonus_bps)| AccumulationTier { threshold, bonus_bps })
+        .collect();
+        entries.sort_by_key(|tier| tier.threshold);
+        validate_accumulation_tiers(&entries)?;
+
+        game_config.accumulation_tier1_threshold = 0;
+        game_config.accumulation_tier2_threshold = 0;
+        game_config.accumulation_tier3_threshold = 0;
+        game_config.accumulation_tier4_threshold = 0;
+        game_config.accumulation_tier5_threshold = 0;
+        game_config.accumulation_tier6_threshold = 0;
+        game_config.accumulation_tier7_threshold = 0;
+        game_config.accumulation_tier8_threshold = 0;
+        game_config.accumulation_tier1_bonus_bps = 0;
+        game_config.accumulation_tier2_bonus_bps = 0;
+        game_config.accumulation_tier3_bonus_bps = 0;
+        game_config.accumulation_tier4_bonus_bps = 0;
+        game_config.accumulation_tier5_bonus_bps = 0;
+        game_config.accumulation_tier6_bonus_bps = 0;
+        game_config.accumulation_tier7_bonus_bps = 0;
+        game_config.accumulation_tier8_bonus_bps = 0;
+
+        let accumulation_tiers = &mut ctx.accounts.accumulation_tiers;
+        accumulation_tiers.entries = entries.clone();
+        accumulation_tiers.bump = ctx.bumps.accumulation_tiers;
+
+        emit!(AccumulationTiersUpdatedEvent {
+            header: next_event_header(game_config)?,
+            entries,
+        });
+
         Ok(())
     }
-}
-
-// ========== HELPER FUNCTIONS ==========
 
-#[inline(never)]
-fn distribute_payment<'info>(
-    amount: u64,
-    from: &Account<'info, TokenAccount>,
-    reward_pool: &Account<'info, TokenAccount>,
-    marketing: &Account<'info, TokenAccount>,
-    dev: &Account<'info, TokenAccount>,
-    authority: &Signer<'info>,
-    token_program: &Program<'info, Token>,
-) -> Result<()> {
-    let to_reward_pool = amount
-        .checked_mul(95)
-        .ok_or(ErrorCode::Overflow)?
-        .checked_div(100)
-        .ok_or(ErrorCode::Overflow)?;
-    let to_marketing = amount
-        .checked_mul(3)
-        .ok_or(ErrorCode::Overflow)?
-        .checked_div(100)
-        .ok_or(ErrorCode::Overflow)?;
-    let to_dev = amount
-        .checked_mul(2)
-        .ok_or(ErrorCode::Overflow)?
-        .checked_div(100)
-        .ok_or(ErrorCode::Overflow)?;
+    /// Replaces the whole tier table. Entries must have strictly ascending,
+    /// non-zero thresholds.
+    pub fn admin_set_accumulation_tiers(
+        ctx: Context<UpdateAccumulationTiers>,
+        entries: Vec<AccumulationTier>,
+    ) -> Result<()> {
+        validate_accumulation_tiers(&entries)?;
 
-    token::transfer(
-        CpiContext::new(
-            token_program.to_account_info(),
-            Transfer {
-                from: from.to_account_info(),
-                to: reward_pool.to_account_info(),
-                authority: authority.to_account_info(),
-            },
-        ),
-        to_reward_pool,
-    )?;
+        ctx.accounts.accumulation_tiers.entries = entries.clone();
 
-    token::transfer(
-        CpiContext::new(
-            token_program.to_account_info(),
-            Transfer {
-                from: from.to_account_info(),
-                to: marketing.to_account_info(),
-                authority: authority.to_account_info(),
-            },
-        ),
-        to_marketing,
-    )?;
+        emit!(AccumulationTiersUpdatedEvent {
+            header: next_event_header(&mut *ctx.accounts.game_config.load_mut()?)?,
+            entries,
+        });
 
-    token::transfer(
-        CpiContext::new(
-            token_program.to_account_info(),
-            Transfer {
-                from: from.to_account_info(),
-                to: dev.to_account_info(),
-                authority: authority.to_account_info(),
-            },
-        ),
-        to_dev,
-    )?;
+        Ok(())
+    }
 
-    Ok(())
-}
+    /// Accumulation bonus the current table pays on `amount` of pending rewards
+    pub fn preview_accumulation_bonus(
+        ctx: Context<PreviewAccumulationBonus>,
+        amount: u64,
+    ) -> Result<u64> {
+        calculate_progressive_bonus(amount, &ctx.accounts.accumulation_tiers.entries)
+    }
 
-#[inline(never)]
-fn calculate_progressive_bonus(pending_rewards: u64, game_config: &GameConfig) -> Result<u64> {
-    let mut total_bonus: u128 = 0;
-    let mut remaining = pending_rewards;
-    
-    let tiers = [
-        (game_config.accumulation_tier8_threshold, game_config.accumulation_tier8_bonus_bps),
-        (game_config.accumulation_tier7_threshold, game_config.accumulation_tier7_bonus_bps),
-        (game_config.accumulation_tier6_threshold, game_config.accumulation_tier6_bonus_bps),
-        (game_config.accumulation_tier5_threshold, game_config.accumulation_tier5_bonus_bps),
-        (game_config.accumulation_tier4_threshold, game_config.accumulation_tier4_bonus_bps),
-        (game_config.accumulation_tier3_threshold, game_config.accumulation_tier3_bonus_bps),
-        (game_config.accumulation_tier2_threshold, game_config.accumulation_tier2_bonus_bps),
-        (game_config.accumulation_tier1_threshold, game_config.accumulation_tier1_bonus_bps),
-    ];
-    
-    for (threshold, bonus_bps) in tiers.iter() {
-        if *threshold > 0 && remaining > *threshold {
-            let amount_in_tier = remaining
-                .checked_sub(*threshold)
-                .ok_or(ErrorCode::Overflow)?;
-            
-            let tier_bonus = (amount_in_tier as u128)
-                .checked_mul(*bonus_bps as u128)
-                .and_then(|r| r.checked_div(10000))
-                .ok_or(ErrorCode::Overflow)?;
-                
-            total_bonus = total_bonus.checked_add(tier_bonus).ok_or(ErrorCode::Overflow)?;
-            remaining = *threshold;
+    /// Checks the player's cached totals against their per-property holdings.
+    /// Pass the player's properties in `remaining_accounts` to include the
+    /// income check; it is skipped unless every held property is supplied.
+    /// The player is settled to now first, so income reflects current rates.
+    pub fn audit_player<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuditPlayer<'info>>,
+    ) -> Result<PlayerAudit> {
+        let game_config = ctx.accounts.game_config.load()?;
+        let mut player = boxed_copy(&*ctx.accounts.player_account.load()?);
+        update_pending_rewards(&mut player, &game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+        let mut properties = Vec::with_capacity(ctx.remaining_accounts.len());
+        for property_info in ctx.remaining_accounts.iter() {
+            properties.push(Account::<Property>::try_from(property_info)?);
         }
+        let properties: Vec<&Property> = properties.iter().map(|p| &**p).collect();
+
+        audit_player_state(&player, &properties)
     }
-    
-    u64::try_from(total_bonus).map_err(|_| ErrorCode::Overflow.into())
-}
 
-fn get_property_bit_in_set(property_id: u8, set_id: u8) -> u8 {
-    match set_id {
-        0 => property_id,
-        1 => property_id.saturating_sub(2),
-        2 => property_id.saturating_sub(5),
-        3 => property_id.saturating_sub(8),
-        4 => property_id.saturating_sub(11),
-        5 => property_id.saturating_sub(14),
-        6 => property_id.saturating_sub(17),
-        7 => property_id.saturating_sub(20),
-        _ => 0,
+    // ========== MIGRATION ==========
+    // Accounts created before the current layouts are rewritten in place.
+    // Migrate the game config first, then every property, then players.
+
+    /// Rewrites the legacy game config in the current layout. Settings it
+    /// predates take their `initialize_game` values.
+    pub fn migrate_game_config(ctx: Context<MigrateGameConfig>) -> Result<()> {
+        let info = ctx.accounts.game_config.to_account_info();
+        let legacy: Box<LegacyGameConfig> = read_legacy_account(&info, GameConfig::DISCRIMINATOR)?;
+        require_keys_eq!(legacy.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+
+        let new_len = 8 + std::mem::size_of::<GameConfig>();
+        grow_account(&info, new_len, &ctx.accounts.authority, &ctx.accounts.system_program)?;
+
+        let mut data = info.try_borrow_mut_data()?;
+        data[8..].fill(0);
+        let game_config: &mut GameConfig = bytemuck::from_bytes_mut(&mut data[8..new_len]);
+        set_default_game_settings(game_config);
+        game_config.authority = legacy.authority;
+        game_config.dev_wallet = legacy.dev_wallet;
+        game_config.marketing_wallet = legacy.marketing_wallet;
+        game_config.token_mint = legacy.token_mint;
+        game_config.reward_pool_vault = legacy.reward_pool_vault;
+        game_config.accumulation_tier1_threshold = legacy.accumulation_tier_thresholds[0];
+        game_config.accumulation_tier2_threshold = legacy.accumulation_tier_thresholds[1];
+        game_config.accumulation_tier3_threshold = legacy.accumulation_tier_thresholds[2];
+        game_config.accumulation_tier4_threshold = legacy.accumulation_tier_thresholds[3];
+        game_config.accumulation_tier5_threshold = legacy.accumulation_tier_thresholds[4];
+        game_config.accumulation_tier6_threshold = legacy.accumulation_tier_thresholds[5];
+        game_config.accumulation_tier7_threshold = legacy.accumulation_tier_thresholds[6];
+        game_config.accumulation_tier8_threshold = legacy.accumulation_tier_thresholds[7];
+        game_config.accumulation_tier1_bonus_bps = legacy.accumulation_tier_bonus_bps[0];
+        game_config.accumulation_tier2_bonus_bps = legacy.accumulation_tier_bonus_bps[1];
+        game_config.accumulation_tier3_bonus_bps = legacy.accumulation_tier_bonus_bps[2];
+        game_config.accumulation_tier4_bonus_bps = legacy.accumulation_tier_bonus_bps[3];
+        game_config.accumulation_tier5_bonus_bps = legacy.accumulation_tier_bonus_bps[4];
+        game_config.accumulation_tier6_bonus_bps = legacy.accumulation_tier_bonus_bps[5];
+        game_config.accumulation_tier7_bonus_bps = legacy.accumulation_tier_bonus_bps[6];
+        game_config.accumulation_tier8_bonus_bps = legacy.accumulation_tier_bonus_bps[7];
+        game_config.set_bonus_bps = legacy.set_bonus_bps;
+        game_config.steal_chance_bps = legacy.steal_chance_bps;
+        game_config.steal_cost_percent_bps = legacy.steal_cost_percent_bps;
+        game_config.game_paused = legacy.game_paused;
+        game_config.bump = legacy.bump;
+        game_config.reward_pool_vault_bump = legacy.reward_pool_vault_bump;
+
+        emit!(AccountMigratedEvent {
+            header: next_event_header(game_config)?,
+            account: info.key(),
+            new_size: new_len as u32,
+        });
+
+        Ok(())
     }
-}
 
-fn get_properties_in_set(set_id: u8) -> u8 {
-    match set_id {
-        0 | 7 => 2,
-        _ => 3,
+    /// Grows a legacy property to the current layout, with every field it
+    /// predates zero (flat pricing, no launch, global steal and sell
+    /// settings), and registers its supply and income rate in the game config.
+    pub fn migrate_property(ctx: Context<MigrateProperty>, property_id: u8) -> Result<()> {
+        let info = ctx.accounts.property.to_account_info();
+        require!(
+            info.data_len() == 8 + LEGACY_PROPERTY_SIZE
+                && info.try_borrow_data()?.starts_with(Property::DISCRIMINATOR),
+            ErrorCode::NotLegacyAccount
+        );
+
+        let new_len = 8 + Property::SIZE;
+        grow_account(&info, new_len, &ctx.accounts.authority, &ctx.accounts.system_program)?;
+        let property = Property::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(property.property_id == property_id, ErrorCode::InvalidPropertyId);
+
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let set_id = property.set_id as usize;
+        game_config.set_total_supply[set_id] = game_config.set_total_supply[set_id]
+            .checked_add(property.max_slots_per_property as u32)
+            .ok_or(ErrorCode::Overflow)?;
+        game_config.total_supply = game_config.total_supply
+            .checked_add(property.max_slots_per_property as u32)
+            .ok_or(ErrorCode::Overflow)?;
+        checkpoint_property_income(game_config, &property, Clock::get()?.unix_timestamp)?;
+
+        emit!(AccountMigratedEvent {
+            header: next_event_header(game_config)?,
+            account: info.key(),
+            new_size: new_len as u32,
+        });
+
+        Ok(())
     }
-}
 
-fn count_properties_in_set(mask: u8) -> u8 {
-    mask.count_ones() as u8
-}
+    /// Rewrites a legacy player account in the current layout. Income up to
+    /// now is settled with the legacy per-second formula, and the set and
+    /// accumulation bonuses the legacy claim would have paid on it are
+    /// credited as bonus rewards. From then on the player accrues from the
+    /// property income indexes, so every property the player holds must
+    /// already be migrated.
+    pub fn migrate_player(ctx: Context<MigratePlayer>) -> Result<()> {
+        let game_config = &mut ctx.accounts.game_config.load_mut()?;
+        let info = ctx.accounts.player_account.to_account_info();
+        let legacy: Box<LegacyPlayerAccount> = read_legacy_account(&info, PlayerAccount::DISCRIMINATOR)?;
+        let now = Clock::get()?.unix_timestamp;
 
-fn is_set_complete(player: &PlayerAccount, set_id: u8) -> bool {
-    let required = get_properties_in_set(set_id);
-    let owned = count_properties_in_set(player.set_properties_mask[set_id as usize]);
-    owned >= required
-}
+        for property_id in 0..MAX_PROPERTIES {
+            require!(
+                legacy.property_slots[property_id] == 0
+                    || game_config.property_income_updated_at[property_id] != 0,
+                ErrorCode::PropertyNotMigrated
+            );
+        }
 
-// ========== ACCOUNT CONTEXTS ==========
+        let elapsed = now.saturating_sub(legacy.last_accumulation_timestamp).max(0);
+        let legacy_pending = (legacy.total_base_daily_income / 86400)
+            .checked_mul(elapsed as u64)
+            .and_then(|income| income.checked_add(legacy.pending_rewards))
+            .ok_or(ErrorCode::Overflow)?;
+        let legacy_bonus = legacy_claim_bonus(&legacy, legacy_pending, game_config)?;
+
+        let new_len = 8 + std::mem::size_of::<PlayerAccount>();
+        grow_account(&info, new_len, &ctx.accounts.authority, &ctx.accounts.system_program)?;
+
+        let mut data = info.try_borrow_mut_data()?;
+        data[8..].fill(0);
+        let player: &mut PlayerAccount = bytemuck::from_bytes_mut(&mut data[8..new_len]);
+        player.owner = legacy.owner;
+        player.total_rewards_claimed = legacy.total_rewards_claimed;
+        player.pending_rewards = legacy_pending;
+        player.pending_bonus_rewards = legacy_bonus;
+        player.last_accumulation_timestamp = now;
+        player.last_claim_timestamp = now;
+        player.total_steals_attempted = legacy.total_steals_attempted;
+        player.total_steals_successful = legacy.total_steals_successful;
+        player.total_slots_owned = legacy.total_slots_owned;
+        player.properties_owned_count = legacy.properties_owned_count;
+        player.bump = legacy.bump;
+        player.property_purchase_timestamp = legacy.property_purchase_timestamp;
+        player.property_last_purchase_timestamp = legacy.property_purchase_timestamp;
+        player.property_shield_expiry = legacy.property_shield_expiry;
+        player.property_shield_cooldown = legacy.property_shield_cooldown;
+        player.property_steal_protection_expiry = legacy.property_steal_protection_expiry;
+        player.set_cooldown_timestamp = legacy.set_cooldown_timestamp;
+        player.set_cooldown_duration = legacy.set_cooldown_duration;
+        player.steal_cooldown_timestamp = legacy.steal_cooldown_timestamp;
+        player.property_slots = legacy.property_slots;
+        player.property_shielded = legacy.property_shielded;
+        player.set_last_purchased_property = legacy.set_last_purchased_property;
+        player.set_properties_mask = legacy.set_properties_mask;
+
+        // Income and set bonuses accrue from here under the current rules
+        for property_id in 0..MAX_PROPERTIES {
+            player.property_income_index[property_id] = income_index_at(game_config, property_id, now)?;
+        }
+        for set_id in 0..MAX_SETS {
+            refresh_set_completion(player, set_id, now)?;
+        }
+        player.total_base_daily_income = current_daily_income(player, game_config)?;
 
-#[derive(Accounts)]
-pub struct InitializeGame<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<GameConfig>(),
-        seeds = [b"game_config"],
+        emit!(AccountMigratedEvent {
+            header: next_event_header(game_config)?,
+            account: info.key(),
+            new_size: new_len as u32,
+        });
+
+        Ok(())
+    }
+
+    // ========== QUOTES ==========
+    // Each quote replays its instruction's checks and arithmetic against
+    // copies of the accounts at the current clock, so simulating it returns
+    // exactly what the instruction would charge or pay, or the same error.
+
+    /// What `claim_rewards` would pay now. Zero means the claim would fail
+    /// with NoRewardsToClaim.
+    pub fn quote_claim(ctx: Context<QuoteClaim>) -> Result<ClaimQuote> {
+        let game_config = ctx.accounts.game_config.load()?;
+        let mut player = boxed_copy(&*ctx.accounts.player_account.load()?);
+
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+
+        update_pending_rewards(&mut player, &game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+        Ok(ClaimQuote {
+            pending_rewards: player.pending_rewards,
+            pending_bonus_rewards: player.pending_bonus_rewards,
+            total: player.pending_rewards
+                .checked_add(player.pending_bonus_rewards)
+                .ok_or(ErrorCode::Overflow)?,
+            daily_income: player.total_base_daily_income,
+        })
+    }
+
+    /// Price of `slots` through `buy_property`, in game tokens
+    pub fn quote_buy(
+        ctx: Context<QuoteProperty>,
+        slots: u16,
+        launch_proof: Vec<[u8; 32]>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<BuyQuote> {
+        let mut game_config = boxed_copy(&*ctx.accounts.game_config.load()?);
+        let mut player = boxed_copy(&*ctx.accounts.player_account.load()?);
+        let mut property = Box::new((*ctx.accounts.property).clone());
+        let clock = Clock::get()?;
+
+        let total_cost = prepare_purchase(
+            &mut player,
+            &mut property,
+            &mut game_config,
+            &ctx.accounts.accumulation_tiers.entries,
+            slots,
+            &launch_proof,
+            &allowlist_proof,
+            clock.unix_timestamp,
+        )?;
+
+        Ok(BuyQuote {
+            slots,
+            total_cost,
+            daily_income_increase: game_config.property_income_rate[property.property_id as usize]
+                .checked_mul(slots as u64)
+                .ok_or(ErrorCode::Overflow)?,
+        })
+    }
+
+    /// Cost and coverage of `activate_shield` for `shield_duration_hours`
+    pub fn quote_shield(
+        ctx: Context<QuoteProperty>,
+        shield_duration_hours: u16,
+    ) -> Result<ShieldQuote> {
+        let game_config = ctx.accounts.game_config.load()?;
+        let property = &ctx.accounts.property;
+        let player = ctx.accounts.player_account.load()?;
+        let clock = Clock::get()?;
+
+        let property_id = property.property_id as usize;
+
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(player.property_slots[property_id] > 0, ErrorCode::DoesNotOwnProperty);
+        require!(
+            (1..=48).contains(&shield_duration_hours),
+            ErrorCode::InvalidShieldDuration
+        );
+        require!(
+            player.property_shielded[property_id] == 0 ||
+            clock.unix_timestamp >= player.property_shield_expiry[property_id],
+            ErrorCode::ShieldAlreadyActive
+        );
+
+        let slots = player.property_slots[property_id];
+        let starts_at = get_shield_start(&player, property_id, clock.unix_timestamp);
+        let shield_duration_seconds = (shield_duration_hours as i64)
+            .checked_mul(3600)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(ShieldQuote {
+            slots,
+            cost: get_shield_cost(property, slots, shield_duration_hours)?,
+            starts_at,
+            expires_at: starts_at
+                .checked_add(shield_duration_seconds)
+                .ok_or(ErrorCode::Overflow)?,
+        })
+    }
+
+    /// Cost and odds of attempting `slots_to_steal` with
+    /// `steal_property_instant`. The target is drawn at random on the real
+    /// call, so only the attacker's side is checked here.
+    pub fn quote_steal(
+        ctx: Context<QuoteProperty>,
+        slots_to_steal: u16,
+    ) -> Result<StealQuote> {
+        let game_config = ctx.accounts.game_config.load()?;
+        let property = &ctx.accounts.property;
+        let player = ctx.accounts.player_account.load()?;
+        let clock = Clock::get()?;
+
+        let property_id = property.property_id as usize;
+        let set_id = property.set_id as usize;
+        let concentration_caps = get_concentration_caps(&game_config, set_id);
+
+        validate_steal_slots(&game_config, slots_to_steal)?;
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(
+            player.property_slots[property_id] < property.max_per_player,
+            ErrorCode::MaxSlotsReached
+        );
+        check_concentration(&player, set_id, concentration_caps, 1)?;
+
+        if player.steal_cooldown_timestamp[property_id] != 0 {
+            let time_since_last_steal = clock.unix_timestamp
+                .checked_sub(player.steal_cooldown_timestamp[property_id])
+                .ok_or(ErrorCode::Overflow)?;
+            require!(
+                time_since_last_steal >= get_steal_cooldown(&game_config, property)?,
+                ErrorCode::StealCooldownActive
+            );
+        }
+
+        Ok(StealQuote {
+            slots: slots_to_steal,
+            cost: get_steal_cost(property, game_config.steal_cost_percent_bps, slots_to_steal)?,
+            chance_bps: game_config.steal_chance_bps,
+            max_slots_gained: property.max_per_player
+                .checked_sub(player.property_slots[property_id])
+                .ok_or(ErrorCode::Overflow)?
+                .min(concentration_room(&player, set_id, concentration_caps))
+                .min(slots_to_steal),
+        })
+    }
+
+    /// What `sell_property` would pay for `slots`, plus the settled rewards
+    /// it would add when called with `claim_rewards`
+    pub fn quote_sell(
+        ctx: Context<QuoteProperty>,
+        slots: u16,
+        claim_rewards: bool,
+    ) -> Result<SellQuote> {
+        let game_config = ctx.accounts.game_config.load()?;
+        let property = &ctx.accounts.property;
+        let mut player = boxed_copy(&*ctx.accounts.player_account.load()?);
+        let clock = Clock::get()?;
+
+        let property_id = property.property_id as usize;
+
+        update_pending_rewards(&mut player, &game_config, &ctx.accounts.accumulation_tiers.entries)?;
+
+        require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+        require!(player.property_slots[property_id] >= slots, ErrorCode::InsufficientSlots);
+
+        let (received, sell_value_bps, days_held) =
+            get_sell_proceeds(&game_config, property, &player, slots, clock.unix_timestamp)?;
+        let rewards_paid = if claim_rewards { take_pending_rewards(&mut player)? } else { 0 };
+
+        Ok(SellQuote {
+            slots,
+            sell_value_bps,
+            days_held,
+            received,
+            rewards_paid,
+        })
+    }
+}
+}
+pub use instructions::*;
+
+// ========== HELPER FUNCTIONS ==========
+
+/// Every tunable setting at its launch value. Used by `initialize_game`
+/// and to fill in settings a migrated legacy config predates.
+fn set_default_game_settings(game_config: &mut GameConfig) {
+    game_config.accumulation_tier1_threshold = 0;
+    game_config.accumulation_tier2_threshold = 0;
+    game_config.accumulation_tier3_threshold = 0;
+    game_config.accumulation_tier4_threshold = 0;
+    game_config.accumulation_tier5_threshold = 0;
+    game_config.accumulation_tier6_threshold = 0;
+    game_config.accumulation_tier7_threshold = 0;
+    game_config.accumulation_tier8_threshold = 0;
+    game_config.steal_chance_bps = 3300; // 33%
+    game_config.steal_cost_percent_bps = 5000; // 50%
+    game_config.referral_bps = 0;
+    game_config.steal_mode = STEAL_MODE_SINGLE;
+    game_config.max_steal_slots = 1;
+    game_config.retaliation_window_seconds = 0;
+    game_config.steal_protection_success_seconds = 6 * 3600;
+    game_config.steal_protection_failed_seconds = 6 * 3600;
+    game_config.steal_cooldown_bps = 5000; // Half the property's purchase cooldown
+    game_config.sell_base_bps = 1500;
+    game_config.sell_max_bps = 3000;
+    game_config.sell_ramp_days = 14;
+    game_config.stake_boost_unit = 0; // Staking disabled until configured
+    game_config.stake_boost_bps_per_unit_day = 0;
+    game_config.stake_max_boost_bps = 0;
+    game_config.stake_early_unlock_penalty_bps = 0;
+    game_config.stake_max_lock_days = 0;
+    game_config.vesting_period_seconds = 0; // Vested claims disabled until configured
+    game_config.vesting_bonus_bps = 0;
+    game_config.total_vesting_liability = 0;
+    game_config.total_referral_liability = 0;
+    game_config.total_insurance_liability = 0;
+    game_config.burn_bps = 0;
+    game_config.total_burned = 0;
+    game_config.event_sequence = 0;
+    game_config.crank_tip_amount = 0; // Crank tips disabled until configured
+    game_config.crank_tip_budget = 0;
+    game_config.crank_min_interval_seconds = 0;
+    game_config.purchase_limit_flags = 0;
+    game_config.property_purchase_cooldown_seconds = 0;
+    game_config.purchase_window_seconds = 0;
+    game_config.allowlist_root = [0u8; 32];
+    game_config.early_access_end = 0;
+    game_config.purchase_window_max_slots = 0;
+    game_config.max_set_share_bps = 0;
+    game_config.max_global_share_bps = 0;
+    game_config.set_total_supply = [0u32; MAX_SETS];
+    game_config.total_supply = 0;
+    game_config.retaliation_cost_bps = 5000; // 50% of a normal steal
+    game_config.insurance_premium_bps = 0;
+    game_config.insurance_coverage_bps = 0;
+    game_config.set_bonus_bps = [
+        3000,  // Set 0 (Brown) - 30%
+        3286,  // Set 1 (Light Blue) - 32.86%
+        3571,  // Set 2 (Pink) - 35.71%
+        3857,  // Set 3 (Orange) - 38.57%
+        4143,  // Set 4 (Red) - 41.43%
+        4429,  // Set 5 (Yellow) - 44.29%
+        4714,  // Set 6 (Green) - 47.14%
+        5000,  // Set 7 (Dark Blue) - 50%
+    ];
+    game_config.accumulation_tier1_bonus_bps = 0;
+    game_config.accumulation_tier2_bonus_bps = 0;
+    game_config.accumulation_tier3_bonus_bps = 0;
+    game_config.accumulation_tier4_bonus_bps = 0;
+    game_config.accumulation_tier5_bonus_bps = 0;
+    game_config.accumulation_tier6_bonus_bps = 0;
+    game_config.accumulation_tier7_bonus_bps = 0;
+    game_config.accumulation_tier8_bonus_bps = 0;
+}
+
+/// Reads an account still in a legacy zero-copy layout `T`, recognised by
+/// its discriminator and exact length
+fn read_legacy_account<T: bytemuck::Pod>(info: &AccountInfo, discriminator: &[u8]) -> Result<Box<T>> {
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() == 8 + std::mem::size_of::<T>() && data.starts_with(discriminator),
+        ErrorCode::NotLegacyAccount
+    );
+    Ok(boxed_copy(bytemuck::from_bytes::<T>(&data[8..])))
+}
+
+/// Bonus the legacy `claim_rewards` paid on `pending`: the accumulation
+/// tiers, read from the tier fields `migrate_game_config` carried over, plus
+/// the set bonus of every set complete in the legacy account
+fn legacy_claim_bonus(legacy: &LegacyPlayerAccount, pending: u64, game_config: &GameConfig) -> Result<u64> {
+    let tiers = [
+        (game_config.accumulation_tier8_threshold, game_config.accumulation_tier8_bonus_bps),
+        (game_config.accumulation_tier7_threshold, game_config.accumulation_tier7_bonus_bps),
+        (game_config.accumulation_tier6_threshold, game_config.accumulation_tier6_bonus_bps),
+        (game_config.accumulation_tier5_threshold, game_config.accumulation_tier5_bonus_bps),
+        (game_config.accumulation_tier4_threshold, game_config.accumulation_tier4_bonus_bps),
+        (game_config.accumulation_tier3_threshold, game_config.accumulation_tier3_bonus_bps),
+        (game_config.accumulation_tier2_threshold, game_config.accumulation_tier2_bonus_bps),
+        (game_config.accumulation_tier1_threshold, game_config.accumulation_tier1_bonus_bps),
+    ];
+
+    let mut bonus: u128 = 0;
+    let mut remaining = pending;
+    for (threshold, bonus_bps) in tiers {
+        if threshold > 0 && remaining > threshold {
+            bonus = ((remaining - threshold) as u128)
+                .checked_mul(bonus_bps as u128)
+                .and_then(|b| bonus.checked_add(b / 10000))
+                .ok_or(ErrorCode::Overflow)?;
+            remaining = threshold;
+        }
+    }
+
+    for set_id in 0..MAX_SETS as u8 {
+        let owned = count_properties_in_set(legacy.set_properties_mask[set_id as usize]);
+        if owned >= get_properties_in_set(set_id) {
+            bonus = (pending as u128)
+                .checked_mul(game_config.set_bonus_bps[set_id as usize] as u128)
+                .and_then(|b| bonus.checked_add(b / 10000))
+                .ok_or(ErrorCode::Overflow)?;
+        }
+    }
+
+    u64::try_from(bonus).map_err(|_| ErrorCode::Overflow.into())
+}
+
+/// Resizes an account being migrated to `new_len`, zero-filling the new
+/// space, with `payer` topping up its rent
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(new_len)?;
+    Ok(())
+}
+
+/// Heap copy of a zero-copy account, so quotes can replay an instruction's
+/// state changes without writing them back. Kept out of line so the
+/// temporary only occupies this frame.
+#[inline(never)]
+fn boxed_copy<T: Copy>(value: &T) -> Box<T> {
+    Box::new(*value)
+}
+
+/// Stamps the next event with the schema version and the next game-wide
+/// sequence number
+fn next_event_header(game_config: &mut GameConfig) -> Result<EventHeader> {
+    game_config.event_sequence = game_config.event_sequence
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(EventHeader {
+        version: EVENT_SCHEMA_VERSION,
+        sequence: game_config.event_sequence,
+        timestamp: Clock::get()?.unix_timestamp,
+    })
+}
+
+fn daily_income_per_slot(property: &Property) -> Result<u64> {
+    property.price
+        .checked_mul(property.yield_percent_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Recomputes every cached total on the player from the per-property
+/// arrays and reports each one that disagrees
+fn audit_player_state(player: &PlayerAccount, properties: &[&Property]) -> Result<PlayerAudit> {
+    let mut violations = Vec::new();
+
+    let mut slots_sum: u32 = 0;
+    let mut held_count: u8 = 0;
+    let mut held_mask: u32 = 0;
+    let mut set_masks = [0u8; MAX_SETS];
+    for property_id in 0..MAX_PROPERTIES {
+        let slots = player.property_slots[property_id];
+        if player.property_shielded[property_id] > slots {
+            violations.push(AuditViolation::ShieldedExceedsSlots { property_id: property_id as u8 });
+        }
+        if player.property_insured_slots[property_id] > slots {
+            violations.push(AuditViolation::InsuredExceedsSlots { property_id: property_id as u8 });
+        }
+        if slots == 0 {
+            continue;
+        }
+        slots_sum += slots as u32;
+        held_count += 1;
+        held_mask |= 1 << property_id;
+    }
+
+    if slots_sum != player.total_slots_owned as u32 {
+        violations.push(AuditViolation::TotalSlotsMismatch {
+            stored: player.total_slots_owned,
+            computed: slots_sum,
+        });
+    }
+    if held_count != player.properties_owned_count {
+        violations.push(AuditViolation::OwnedCountMismatch {
+            stored: player.properties_owned_count,
+            computed: held_count,
+        });
+    }
+
+    let mut complete_sets: u8 = 0;
+    for set_id in 0..MAX_SETS as u8 {
+        let first = get_set_first_property(set_id);
+        for offset in 0..get_properties_in_set(set_id) {
+            if held_mask & (1 << (first + offset)) != 0 {
+                set_masks[set_id as usize] |= 1 << offset;
+            }
+        }
+        let stored = player.set_properties_mask[set_id as usize];
+        if stored != set_masks[set_id as usize] {
+            violations.push(AuditViolation::SetMaskMismatch {
+                set_id,
+                stored,
+                computed: set_masks[set_id as usize],
+            });
+        }
+
+        let complete = count_properties_in_set(set_masks[set_id as usize]) >= get_properties_in_set(set_id);
+        if complete {
+            complete_sets += 1;
+        }
+        if complete != (player.set_completed_at[set_id as usize] > 0) {
+            violations.push(AuditViolation::SetCompletionStale { set_id });
+        }
+    }
+    if complete_sets != player.complete_sets_owned {
+        violations.push(AuditViolation::CompleteSetsMismatch {
+            stored: player.complete_sets_owned,
+            computed: complete_sets,
+        });
+    }
+
+    // Income can only be recomputed when every held property was supplied
+    let mut supplied_mask: u32 = 0;
+    let mut income: u64 = 0;
+    for property in properties {
+        let property_id = property.property_id as usize;
+        require!(property_id < MAX_PROPERTIES, ErrorCode::InvalidPropertyId);
+        if supplied_mask & (1 << property_id) != 0 {
+            continue;
+        }
+        supplied_mask |= 1 << property_id;
+        income = daily_income_per_slot(property)?
+            .checked_mul(player.property_slots[property_id] as u64)
+            .and_then(|i| income.checked_add(i))
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    let income_checked = held_mask & !supplied_mask == 0;
+    if income_checked && income != player.total_base_daily_income {
+        violations.push(AuditViolation::IncomeMismatch {
+            stored: player.total_base_daily_income,
+            computed: income,
+        });
+    }
+
+    Ok(PlayerAudit {
+        player: player.owner,
+        income_checked,
+        violations,
+    })
+}
+
+fn player_snapshot(player: &PlayerAccount) -> Result<PlayerSnapshot> {
+    Ok(PlayerSnapshot {
+        total_slots_owned: player.total_slots_owned,
+        properties_owned_count: player.properties_owned_count,
+        complete_sets_owned: player.complete_sets_owned,
+        total_base_daily_income: player.total_base_daily_income,
+        pending_rewards: player.pending_rewards
+            .checked_add(player.pending_bonus_rewards)
+            .ok_or(ErrorCode::Overflow)?,
+        stake_boost_bps: player.stake_boost_bps,
+    })
+}
+
+/// Splits a payment 95/3/2 between reward pool, marketing and dev.
+/// `referral_bps` of the payment is taken out of the marketing cut and held
+/// in the reward pool for the referrer; the held amount is returned so the
+/// caller can credit it. `burn_bps` of the payment is burned from the payer
+/// out of the reward pool's cut.
+#[inline(never)]
+fn distribute_payment<'info>(
+    amount: u64,
+    referral_bps: u16,
+    game_config: &mut GameConfig,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    reward_pool: &InterfaceAccount<'info, TokenAccount>,
+    marketing: &InterfaceAccount<'info, TokenAccount>,
+    dev: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: &Signer<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<u64> {
+    let pool_balance_before = token_balance(&reward_pool.to_account_info())?;
+    let to_burn = amount
+        .checked_mul(game_config.burn_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow)?;
+    let to_referrer = amount
+        .checked_mul(referral_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow)?;
+    let to_reward_pool = reward_pool_share(amount)?
+        .checked_add(to_referrer)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_sub(to_burn)
+        .ok_or(ErrorCode::Overflow)?;
+    let to_marketing = amount
+        .checked_mul(3)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(100)
+        .ok_or(ErrorCode::Overflow)?
+        .saturating_sub(to_referrer);
+    let to_dev = amount
+        .checked_mul(2)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(100)
+        .ok_or(ErrorCode::Overflow)?;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: reward_pool.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        to_reward_pool,
+        mint.decimals,
+    )?;
+
+    // Transfer-fee mints deliver less than was sent; the referral share
+    // held in the pool is scaled to what actually arrived.
+    let pool_received = token_balance(&reward_pool.to_account_info())?
+        .checked_sub(pool_balance_before)
+        .ok_or(ErrorCode::Overflow)?;
+    let to_referrer = if pool_received < to_reward_pool {
+        (to_referrer as u128)
+            .checked_mul(pool_received as u128)
+            .and_then(|r| r.checked_div(to_reward_pool as u128))
+            .and_then(|r| u64::try_from(r).ok())
+            .ok_or(ErrorCode::Overflow)?
+    } else {
+        to_referrer
+    };
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: marketing.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        to_marketing,
+        mint.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: dev.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        to_dev,
+        mint.decimals,
+    )?;
+
+    if to_burn > 0 {
+        token_interface::burn(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Burn {
+                    mint: mint.to_account_info(),
+                    from: from.to_account_info(),
+                    authority: authority.to_account_info(),
+                },
+            ),
+            to_burn,
+        )?;
+
+        game_config.total_burned = game_config.total_burned
+            .checked_add(to_burn)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(TokensBurnedEvent {
+            header: next_event_header(game_config)?,
+            payer: authority.key(),
+            amount: to_burn,
+            total_burned: game_config.total_burned,
+        });
+    }
+
+    Ok(to_referrer)
+}
+
+/// Part of a payment that funds the reward pool, before burn and referral
+fn reward_pool_share(amount: u64) -> Result<u64> {
+    amount
+        .checked_mul(95)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(100)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Live balance of a token account, read from account data so it reflects
+/// CPIs made earlier in the instruction
+fn token_balance(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+/// Follows the chain above a new player's referrer, starting at `next`, and
+/// rejects it if it leads back to `player`. `ancestors` holds the chain's
+/// player accounts nearest first and may only stop where the chain does.
+fn check_referral_chain<'info>(
+    player: &Pubkey,
+    mut next: Pubkey,
+    ancestors: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let mut ancestors = ancestors.iter();
+    for _ in 0..MAX_REFERRAL_DEPTH {
+        require!(next != *player, ErrorCode::ReferralCycle);
+        if next == Pubkey::default() {
+            break;
+        }
+
+        let ancestor = ancestors.next().ok_or(ErrorCode::InvalidReferrerAccount)?;
+        let (expected, _) = Pubkey::find_program_address(&[b"player", next.as_ref()], &crate::ID);
+        require_keys_eq!(ancestor.key(), expected, ErrorCode::InvalidReferrerAccount);
+        // A closed account ends the chain
+        if ancestor.data_is_empty() {
+            break;
+        }
+        let loader: AccountLoader<'info, PlayerAccount> = AccountLoader::try_from(ancestor)?;
+        next = loader.load()?.referrer;
+    }
+    Ok(())
+}
+
+/// Referral share applicable to a payment by `player`. A referred player
+/// must pass their referrer's player account; the share is only dropped
+/// once the referrer has closed it.
+fn get_referral_bps(
+    player: &PlayerAccount,
+    referrer_account: &Option<UncheckedAccount>,
+    referral_bps: u16,
+) -> Result<u16> {
+    if player.referrer == Pubkey::default() {
+        return Ok(0);
+    }
+    let account = referrer_account.as_ref().ok_or(ErrorCode::ReferrerAccountRequired)?;
+    let (expected, _) = Pubkey::find_program_address(&[b"player", player.referrer.as_ref()], &crate::ID);
+    require_keys_eq!(account.key(), expected, ErrorCode::InvalidReferrerAccount);
+    if account.data_is_empty() {
+        return Ok(0);
+    }
+    require!(
+        load_player_account(account)?.owner == player.referrer,
+        ErrorCode::InvalidReferrerAccount
+    );
+    Ok(referral_bps)
+}
+
+/// Borrows a player account passed unchecked, after checking it is one
+fn load_player_account<'a>(account: &'a AccountInfo) -> Result<RefMut<'a, PlayerAccount>> {
+    require_keys_eq!(*account.owner, crate::ID, ErrorCode::InvalidReferrerAccount);
+    let data = account.try_borrow_mut_data()?;
+    require!(
+        data.len() == 8 + std::mem::size_of::<PlayerAccount>() && data.starts_with(PlayerAccount::DISCRIMINATOR),
+        ErrorCode::InvalidReferrerAccount
+    );
+    Ok(RefMut::map(data, |data| bytemuck::from_bytes_mut(&mut data[8..])))
+}
+
+/// Credits the referral share to the account `get_referral_bps` accepted
+#[inline(never)]
+fn credit_referral(
+    referrer_account: &Option<UncheckedAccount>,
+    referee: Pubkey,
+    amount: u64,
+    game_config: &mut GameConfig,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let Some(account) = referrer_account else {
+        return Ok(());
+    };
+    credit_referrer(&mut *load_player_account(account)?, referee, amount, game_config)
+}
+
+fn credit_referrer(
+    referrer: &mut PlayerAccount,
+    referee: Pubkey,
+    amount: u64,
+    game_config: &mut GameConfig,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    referrer.referral_rewards_pending = referrer.referral_rewards_pending
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    referrer.total_referral_earnings = referrer.total_referral_earnings
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    game_config.total_referral_liability = game_config.total_referral_liability
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+
+    emit!(ReferralCreditEvent {
+        header: next_event_header(game_config)?,
+        referrer: referrer.owner,
+        referee,
+        amount,
+        total_referral_earnings: referrer.total_referral_earnings,
+    });
+
+    Ok(())
+}
+
+#[inline(never)]
+/// Each tier pays its bps on the slice of `pending_rewards` between its
+/// threshold and the next tier up. Tiers are stored in ascending order.
+fn calculate_progressive_bonus(pending_rewards: u64, tiers: &[AccumulationTier]) -> Result<u64> {
+    let mut total_bonus: u128 = 0;
+    let mut remaining = pending_rewards;
+    
+    for tier in tiers.iter().rev() {
+        if remaining > tier.threshold {
+            let amount_in_tier = remaining
+                .checked_sub(tier.threshold)
+                .ok_or(ErrorCode::Overflow)?;
+            
+            let tier_bonus = (amount_in_tier as u128)
+                .checked_mul(tier.bonus_bps as u128)
+                .and_then(|r| r.checked_div(10000))
+                .ok_or(ErrorCode::Overflow)?;
+                
+            total_bonus = total_bonus.checked_add(tier_bonus).ok_or(ErrorCode::Overflow)?;
+            remaining = tier.threshold;
+        }
+    }
+    
+    u64::try_from(total_bonus).map_err(|_| ErrorCode::Overflow.into())
+}
+
+/// Runs every buy rule for `slots` of `property` and returns the price in
+/// game tokens. Settles pending rewards and draws down any allowlist
+/// allocation, so it must only be called by an instruction that then pays.
+#[inline(never)]
+fn prepare_purchase(
+    player: &mut PlayerAccount,
+    property: &mut Property,
+    game_config: &mut GameConfig,
+    tiers: &[AccumulationTier],
+    slots: u16,
+    launch_proof: &[[u8; 32]],
+    allowlist_proof: &Option<AllowlistProof>,
+    now: i64,
+) -> Result<u64> {
+    let property_id = property.property_id as usize;
+    let set_id = property.set_id as usize;
+
+    update_pending_rewards(player, game_config, tiers)?;
+
+    require!(game_config.game_paused == 0, ErrorCode::GamePaused);
+    require!(slots > 0, ErrorCode::InvalidSlotAmount);
+    require!(property.available_slots >= slots, ErrorCode::NoSlotsAvailable);
+    
+    require!(
+        player.property_slots[property_id].checked_add(slots).ok_or(ErrorCode::Overflow)? <= property.max_per_player,
+        ErrorCode::MaxSlotsReached
+    );
+
+    check_purchase_limits(player, property, game_config, slots, now)?;
+    check_concentration(player, set_id, get_concentration_caps(game_config, set_id), slots)?;
+
+    // During early access every buy draws down the wallet's allocation
+    if is_early_access_active(game_config, now) {
+        let allocation = verify_allowlist_proof(game_config, &player.owner, allowlist_proof)?;
+        let used = player.allowlist_slots_used
+            .checked_add(slots)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(used <= allocation, ErrorCode::AllowlistAllocationExceeded);
+        player.allowlist_slots_used = used;
+    }
+
+    let held = player.property_slots[property_id];
+    let total_price = match apply_launch_rules(property, &player.owner, held, slots, launch_proof, now)? {
+        Some(auction_price) => auction_price
+            .checked_mul(slots as u64)
+            .ok_or(ErrorCode::Overflow)?,
+        None => get_curve_cost(property, get_slots_sold(property)?, slots)?,
+    };
+
+    Ok(total_price)
+}
+
+/// Credits paid-for slots to the buyer along with their income and set state
+#[inline(never)]
+fn apply_purchase(
+    player: &mut PlayerAccount,
+    property: &mut Property,
+    game_config: &mut GameConfig,
+    slots: u16,
+    total_price: u64,
+    now: i64,
+) -> Result<()> {
+    let property_id = property.property_id as usize;
+    let set_id = property.set_id as usize;
+
+    property.available_slots = property.available_slots
+        .checked_sub(slots)
+        .ok_or(ErrorCode::Overflow)?;
+
+    record_acquisition(player, property_id, slots, now)?;
+    let is_new_ownership = player.property_slots[property_id] == 0;
+    if is_new_ownership {
+        player.properties_owned_count = player.properties_owned_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        
+        let property_bit = get_property_bit_in_set(property.property_id, property.set_id);
+        player.set_properties_mask[set_id] |= 1 << property_bit;
+    }
+    
+    player.property_slots[property_id] = player.property_slots[property_id]
+        .checked_add(slots)
+        .ok_or(ErrorCode::Overflow)?;
+
+    player.total_slots_owned = player.total_slots_owned
+        .checked_add(slots)
+        .ok_or(ErrorCode::Overflow)?;
+
+    player.total_base_daily_income = current_daily_income(player, game_config)?;
+
+    refresh_set_completion(player, set_id, now)?;
+
+    record_purchase(player, property, game_config, slots, now)?;
+
+    emit!(PropertyBoughtEvent {
+        header: next_event_header(game_config)?,
+        player: player.owner,
+        property_id: property.property_id,
+        price: property.price,
+        slots,
+        total_cost: total_price,
+        slots_owned: player.property_slots[property_id],
+        available_slots: property.available_slots,
+        player_state: player_snapshot(player)?,
+    });
+
+    Ok(())
+}
+
+/// Converts a game-token amount to payment-token base units at the posted
+/// rate, rounding up so the vault is never short-changed.
+fn convert_to_payment_amount(
+    game_amount: u64,
+    game_decimals: u8,
+    payment_mint: &PaymentMint,
+    payment_decimals: u8,
+) -> Result<u64> {
+    let exponent = payment_mint.expo as i64 + payment_decimals as i64 - game_decimals as i64;
+    let base = (game_amount as u128)
+        .checked_mul(payment_mint.price as u128)
+        .ok_or(ErrorCode::Overflow)?;
+    let scale = 10u128
+        .checked_pow(exponent.unsigned_abs() as u32)
+        .ok_or(ErrorCode::Overflow)?;
+    let amount = if exponent >= 0 {
+        base.checked_mul(scale).ok_or(ErrorCode::Overflow)?
+    } else {
+        base.div_ceil(scale)
+    };
+    u64::try_from(amount).map_err(|_| ErrorCode::Overflow.into())
+}
+
+/// Reward pool balance not already owed to vesting schedules, referrers or
+/// insured players
+fn available_reward_pool(vault: &TokenAccount, game_config: &GameConfig) -> u64 {
+    vault.amount
+        .saturating_sub(game_config.total_vesting_liability)
+        .saturating_sub(game_config.total_referral_liability)
+        .saturating_sub(game_config.total_insurance_liability)
+}
+
+/// Releases what each tranche has vested since the last settlement and
+/// moves the settlement time to `now`. Tranches vest independently, so a new
+/// claim never delays an older one.
+fn settle_vesting(vesting: &mut VestingAccount, now: i64) -> Result<()> {
+    if now <= vesting.start_time {
+        return Ok(());
+    }
+    if vesting.locked_amount == 0 {
+        vesting.start_time = now;
+        return Ok(());
+    }
+
+    let start = vesting.start_time;
+    let mut oldest = vesting.locked_amount
+        .checked_sub(newer_tranches_amount(vesting)?)
+        .ok_or(ErrorCode::Overflow)?;
+    let mut vested = vest_tranche(&mut oldest, start, vesting.end_time, now)?;
+    for tranche in vesting.tranches.iter_mut() {
+        vested = vested
+            .checked_add(vest_tranche(&mut tranche.amount, start, tranche.end_time, now)?)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+
+    vesting.released_amount = vesting.released_amount
+        .checked_add(vested)
+        .ok_or(ErrorCode::Overflow)?;
+    vesting.locked_amount = vesting.locked_amount
+        .checked_sub(vested)
+        .ok_or(ErrorCode::Overflow)?;
+    vesting.start_time = now;
+
+    // Once the oldest tranche is done, a newer one takes its place
+    if oldest == 0 {
+        if let Some(next) = vesting.tranches.iter().position(|t| t.amount > 0) {
+            vesting.end_time = vesting.tranches[next].end_time;
+            vesting.tranches[next] = VestingTranche::default();
+        }
+    }
+
+    Ok(())
+}
+
+/// Vests `amount` linearly from `start` to `end` and returns the part due at `now`
+fn vest_tranche(amount: &mut u64, start: i64, end: i64, now: i64) -> Result<u64> {
+    if *amount == 0 {
+        return Ok(0);
+    }
+    let vested = if now >= end {
+        *amount
+    } else {
+        let elapsed = now.checked_sub(start).ok_or(ErrorCode::Overflow)?;
+        let duration = end.checked_sub(start).ok_or(ErrorCode::Overflow)?;
+        (*amount as u128)
+            .checked_mul(elapsed as u128)
+            .and_then(|v| v.checked_div(duration as u128))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(ErrorCode::Overflow)?
+    };
+    *amount = amount.checked_sub(vested).ok_or(ErrorCode::Overflow)?;
+    Ok(vested)
+}
+
+fn newer_tranches_amount(vesting: &VestingAccount) -> Result<u64> {
+    vesting.tranches
+        .iter()
+        .try_fold(0u64, |sum, t| sum.checked_add(t.amount))
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Locks a new claim on its own schedule ending at `end_time`. Call after
+/// `settle_vesting`; a claim ending with an existing tranche joins it.
+fn add_vesting_tranche(vesting: &mut VestingAccount, amount: u64, end_time: i64) -> Result<()> {
+    if vesting.locked_amount == 0 || vesting.end_time == end_time {
+        vesting.end_time = end_time;
+    } else if let Some(index) = vesting.tranches
+        .iter()
+        .position(|t| t.amount > 0 && t.end_time == end_time)
+        .or_else(|| vesting.tranches.iter().position(|t| t.amount == 0))
+    {
+        let tranche = &mut vesting.tranches[index];
+        tranche.amount = tranche.amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        tranche.end_time = end_time;
+    } else {
+        return err!(ErrorCode::VestingTranchesFull);
+    }
+
+    vesting.locked_amount = vesting.locked_amount
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+fn calculate_stake_boost(game_config: &GameConfig, amount: u64, lock_days: u16) -> Result<u16> {
+    let units = amount
+        .checked_div(game_config.stake_boost_unit)
+        .ok_or(ErrorCode::StakingDisabled)?;
+    let boost = (units as u128)
+        .checked_mul(lock_days as u128)
+        .and_then(|b| b.checked_mul(game_config.stake_boost_bps_per_unit_day as u128))
+        .ok_or(ErrorCode::Overflow)?
+        .min(game_config.stake_max_boost_bps as u128);
+    Ok(boost as u16)
+}
+
+fn validate_accumulation_tiers(entries: &[AccumulationTier]) -> Result<()> {
+    require!(entries.len() <= MAX_ACCUMULATION_TIERS, ErrorCode::InvalidAccumulationTiers);
+
+    let mut previous_threshold = 0u64;
+    for tier in entries {
+        require!(tier.threshold > previous_threshold, ErrorCode::InvalidAccumulationTiers);
+        require!(tier.bonus_bps <= 5000, ErrorCode::InvalidBonus);
+        previous_threshold = tier.threshold;
+    }
+    Ok(())
+}
+
+fn validate_steal_slots(game_config: &GameConfig, slots_to_steal: u16) -> Result<()> {
+    if game_config.steal_mode == STEAL_MODE_MULTI {
+        require!(
+            slots_to_steal > 0 && slots_to_steal <= game_config.max_steal_slots,
+            ErrorCode::InvalidStealSlots
+        );
+    } else {
+        require!(slots_to_steal == 1, ErrorCode::InvalidStealSlots);
+    }
+    Ok(())
+}
+
+#[inline(never)]
+fn generate_steal_entropy(
+    slot_hashes: &AccountInfo,
+    user_randomness: &[u8; 32],
+    clock: &Clock,
+) -> Result<[u8; 32]> {
+    let slot_hashes_data = slot_hashes.data.borrow();
+
+    require!(
+        slot_hashes_data.len() >= 40,
+        ErrorCode::SlotHashUnavailable
+    );
+
+    let mut slot_hash_bytes = [0u8; 32];
+    slot_hash_bytes.copy_from_slice(&slot_hashes_data[8..40]);
+
+    let mut combined_entropy = [0u8; 32];
+    for i in 0..32 {
+        combined_entropy[i] = user_randomness[i]
+            ^ slot_hash_bytes[i]
+            ^ ((clock.slot >> (i % 8)) as u8)
+            ^ ((clock.unix_timestamp >> (i % 8)) as u8);
+    }
+
+    Ok(combined_entropy)
+}
+
+/// Applies the launch auction rules to a buy. Returns the auction price per
+/// slot while the auction runs, or None once the property trades at normal
+/// pricing.
+#[inline(never)]
+fn apply_launch_rules(
+    property: &Property,
+    buyer: &Pubkey,
+    held: u16,
+    slots: u16,
+    proof: &[[u8; 32]],
+    now: i64,
+) -> Result<Option<u64>> {
+    if property.launch_stage == LAUNCH_NONE
+        || property.launch_stage == LAUNCH_ENDED
+        || now >= property.launch_end_time
+    {
+        return Ok(None);
+    }
+
+    require!(now >= property.launch_start_time, ErrorCode::LaunchNotStarted);
+
+    let auction_price = get_launch_price(property, now)?;
+
+    if property.launch_wallet_cap > 0 {
+        require!(
+            held.checked_add(slots).ok_or(ErrorCode::Overflow)? <= property.launch_wallet_cap,
+            ErrorCode::LaunchWalletCapReached
+        );
+    }
+    if property.launch_merkle_root != [0u8; 32] {
+        let leaf = hashv(&[buyer.as_ref()]).to_bytes();
+        require!(
+            verify_merkle_proof(proof, property.launch_merkle_root, leaf),
+            ErrorCode::NotAllowlisted
+        );
+    }
+
+    Ok(Some(auction_price))
+}
+
+/// Moves the property to the launch stage in effect at `now`. Stage changes
+/// are recorded by the first buy after them but stamped with the scheduled
+/// time they took effect.
+fn advance_launch_stage(property: &mut Property, game_config: &mut GameConfig, now: i64) -> Result<()> {
+    if property.launch_stage == LAUNCH_NONE || property.launch_stage == LAUNCH_ENDED {
+        return Ok(());
+    }
+
+    if now >= property.launch_end_time {
+        property.launch_stage = LAUNCH_ENDED;
+        emit!(PropertyLaunchEvent {
+            header: next_event_header(game_config)?,
+            property_id: property.property_id,
+            stage: LAUNCH_ENDED,
+            price: property.price,
+            timestamp: property.launch_end_time,
+        });
+    } else if property.launch_stage == LAUNCH_SCHEDULED {
+        property.launch_stage = LAUNCH_AUCTION;
+        emit!(PropertyLaunchEvent {
+            header: next_event_header(game_config)?,
+            property_id: property.property_id,
+            stage: LAUNCH_AUCTION,
+            price: property.launch_start_price,
+            timestamp: property.launch_start_time,
+        });
+    }
+
+    Ok(())
+}
+
+fn is_early_access_active(game_config: &GameConfig, now: i64) -> bool {
+    game_config.allowlist_root != [0u8; 32] && now < game_config.early_access_end
+}
+
+/// Verifies an allowlist proof for `wallet` and returns its slot allocation
+fn verify_allowlist_proof(
+    game_config: &GameConfig,
+    wallet: &Pubkey,
+    allowlist_proof: &Option<AllowlistProof>,
+) -> Result<u16> {
+    let entry = allowlist_proof.as_ref().ok_or(ErrorCode::NotAllowlisted)?;
+    let leaf = hashv(&[wallet.as_ref(), &entry.allocation.to_le_bytes()]).to_bytes();
+    require!(
+        verify_merkle_proof(&entry.proof, game_config.allowlist_root, leaf),
+        ErrorCode::NotAllowlisted
+    );
+    Ok(entry.allocation)
+}
+
+/// Auction price decaying linearly from the start price to `price`
+fn get_launch_price(property: &Property, now: i64) -> Result<u64> {
+    let duration = property.launch_end_time
+        .checked_sub(property.launch_start_time)
+        .ok_or(ErrorCode::Overflow)?;
+    let elapsed = now
+        .checked_sub(property.launch_start_time)
+        .ok_or(ErrorCode::Overflow)?
+        .clamp(0, duration);
+    let decay = (property.launch_start_price.saturating_sub(property.price) as u128)
+        .checked_mul(elapsed as u128)
+        .and_then(|r| r.checked_div(duration as u128))
+        .ok_or(ErrorCode::Overflow)?;
+
+    Ok(property.launch_start_price - decay as u64)
+}
+
+/// Sorted-pair sha256 Merkle proof verification
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof {
+        computed = if computed <= *node {
+            hashv(&[&computed, node]).to_bytes()
+        } else {
+            hashv(&[node, &computed]).to_bytes()
+        };
+    }
+    computed == root
+}
+
+fn get_slots_sold(property: &Property) -> Result<u16> {
+    property.max_slots_per_property
+        .checked_sub(property.available_slots)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Total price of `count` slots starting at the `first`-th slot sold, summed
+/// over the property's pricing curve. Used for buys and, from the top of
+/// the curve down, for sell value.
+#[inline(never)]
+fn get_curve_cost(property: &Property, first: u16, count: u16) -> Result<u64> {
+    let price = property.price as u128;
+    let n = count as u128;
+
+    let total = match property.pricing_mode {
+        PRICING_LINEAR if count > 0 => {
+            // n * price + slope * (first + (first + 1) + ... + (first + n - 1))
+            let steps = n * (2 * first as u128 + n - 1) / 2;
+            (property.curve_param as u128)
+                .checked_mul(steps)
+                .and_then(|r| r.checked_add(price * n))
+                .ok_or(ErrorCode::Overflow)?
+        }
+        PRICING_EXPONENTIAL if count > 0 && property.curve_param > 0 => {
+            // Geometric series: price * r^first * (r^n - 1) / (r - 1)
+            let growth = property.curve_param as u128 * CURVE_SCALE / 10000;
+            let ratio = CURVE_SCALE + growth;
+            let first_price = price
+                .checked_mul(pow_scaled(ratio, first)?)
+                .ok_or(ErrorCode::Overflow)?
+                / CURVE_SCALE;
+            first_price
+                .checked_mul(pow_scaled(ratio, count)? - CURVE_SCALE)
+                .ok_or(ErrorCode::Overflow)?
+                / growth
+        }
+        _ => price * n,
+    };
+
+    u64::try_from(total).map_err(|_| ErrorCode::Overflow.into())
+}
+
+/// `base^exp` for a CURVE_SCALE fixed-point base
+fn pow_scaled(mut base: u128, mut exp: u16) -> Result<u128> {
+    let mut result = CURVE_SCALE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base).ok_or(ErrorCode::Overflow)? / CURVE_SCALE;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base).ok_or(ErrorCode::Overflow)? / CURVE_SCALE;
+        }
+    }
+    Ok(result)
+}
+
+/// Enforces the purchase rate limits enabled in `purchase_limit_flags`.
+/// The set cooldown always applies when switching to another property of
+/// the set; PURCHASE_LIMIT_SET extends it to the same property as well.
+#[inline(never)]
+fn check_purchase_limits(
+    player: &PlayerAccount,
+    property: &Property,
+    game_config: &GameConfig,
+    slots: u16,
+    now: i64,
+) -> Result<()> {
+    let property_id = property.property_id as usize;
+    let set_id = property.set_id as usize;
+    let flags = game_config.purchase_limit_flags;
+
+    if player.set_cooldown_timestamp[set_id] != 0
+        && (flags & PURCHASE_LIMIT_SET != 0
+            || player.set_last_purchased_property[set_id] != property.property_id)
+    {
+        let time_since_last_purchase = now
+            .checked_sub(player.set_cooldown_timestamp[set_id])
+            .ok_or(ErrorCode::Overflow)?;
+        if time_since_last_purchase < player.set_cooldown_duration[set_id] {
+            msg!(
+                "Set cooldown active: {} seconds remaining",
+                player.set_cooldown_duration[set_id] - time_since_last_purchase
+            );
+            return err!(ErrorCode::CooldownActive);
+        }
+    }
+
+    if flags & PURCHASE_LIMIT_PROPERTY != 0 && player.property_last_purchase_timestamp[property_id] != 0 {
+        let time_since_last_purchase = now
+            .checked_sub(player.property_last_purchase_timestamp[property_id])
+            .ok_or(ErrorCode::Overflow)?;
+        if time_since_last_purchase < game_config.property_purchase_cooldown_seconds {
+            msg!(
+                "Property cooldown active: {} seconds remaining",
+                game_config.property_purchase_cooldown_seconds - time_since_last_purchase
+            );
+            return err!(ErrorCode::PropertyCooldownActive);
+        }
+    }
+
+    if flags & PURCHASE_LIMIT_GLOBAL != 0 {
+        let used = rolling_window_usage(player, game_config, now)?;
+        require!(
+            used.checked_add(slots as u64).ok_or(ErrorCode::Overflow)?
+                <= game_config.purchase_window_max_slots as u64,
+            ErrorCode::PurchaseBudgetExceeded
+        );
+    }
+
+    Ok(())
+}
+
+/// Slots bought in the rolling window ending at `now`. Windows are aligned
+/// to multiples of `purchase_window_seconds`; the previous window's count is
+/// weighted by how much of it still overlaps the rolling window.
+fn rolling_window_usage(player: &PlayerAccount, game_config: &GameConfig, now: i64) -> Result<u64> {
+    let window = game_config.purchase_window_seconds;
+    let current_start = now - now.rem_euclid(window);
+
+    let (current, previous) = if player.purchase_window_start == current_start {
+        (player.purchase_window_slots, player.purchase_window_prev_slots)
+    } else if player.purchase_window_start == current_start - window {
+        (0, player.purchase_window_slots)
+    } else {
+        (0, 0)
+    };
+
+    let remaining_overlap = window - (now - current_start);
+    let weighted_previous = (previous as u64)
+        .checked_mul(remaining_overlap as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(window as u64)
+        .ok_or(ErrorCode::Overflow)?;
+
+    Ok(weighted_previous + current as u64)
+}
+
+fn record_purchase(
+    player: &mut PlayerAccount,
+    property: &Property,
+    game_config: &GameConfig,
+    slots: u16,
+    now: i64,
+) -> Result<()> {
+    let property_id = property.property_id as usize;
+    let set_id = property.set_id as usize;
+
+    player.set_cooldown_timestamp[set_id] = now;
+    player.set_cooldown_duration[set_id] = property.cooldown_seconds;
+    player.set_last_purchased_property[set_id] = property.property_id;
+    player.property_last_purchase_timestamp[property_id] = now;
+
+    if game_config.purchase_limit_flags & PURCHASE_LIMIT_GLOBAL != 0 {
+        let window = game_config.purchase_window_seconds;
+        let current_start = now - now.rem_euclid(window);
+
+        if player.purchase_window_start != current_start {
+            player.purchase_window_prev_slots = if player.purchase_window_start == current_start - window {
+                player.purchase_window_slots
+            } else {
+                0
+            };
+            player.purchase_window_slots = 0;
+            player.purchase_window_start = current_start;
+        }
+        player.purchase_window_slots = player.purchase_window_slots
+            .checked_add(slots)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+
+    Ok(())
+}
+
+/// Slot caps (set, global) derived from the concentration limits.
+/// `None` means the cap is disabled.
+fn get_concentration_caps(game_config: &GameConfig, set_id: usize) -> (Option<u32>, Option<u32>) {
+    let share_of = |supply: u32, bps: u16| {
+        (bps > 0).then(|| (supply as u64 * bps as u64 / 10000) as u32)
+    };
+    (
+        share_of(game_config.set_total_supply[set_id], game_config.max_set_share_bps),
+        share_of(game_config.total_supply, game_config.max_global_share_bps),
+    )
+}
+
+fn get_set_slots_owned(player: &PlayerAccount, set_id: usize) -> u32 {
+    let first = get_set_first_property(set_id as u8) as usize;
+    let count = get_properties_in_set(set_id as u8) as usize;
+    player.property_slots[first..first + count]
+        .iter()
+        .map(|slots| *slots as u32)
+        .sum()
+}
+
+fn check_concentration(
+    player: &PlayerAccount,
+    set_id: usize,
+    caps: (Option<u32>, Option<u32>),
+    slots: u16,
+) -> Result<()> {
+    let (set_cap, global_cap) = caps;
+
+    if let Some(cap) = set_cap {
+        let held_after = get_set_slots_owned(player, set_id) + slots as u32;
+        if held_after > cap {
+            return Err(error!(ErrorCode::SetConcentrationCapExceeded).with_values((held_after, cap)));
+        }
+    }
+    if let Some(cap) = global_cap {
+        let held_after = player.total_slots_owned as u32 + slots as u32;
+        if held_after > cap {
+            return Err(error!(ErrorCode::GlobalConcentrationCapExceeded).with_values((held_after, cap)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Slots the player can still acquire in the set before hitting a cap
+fn concentration_room(player: &PlayerAccount, set_id: usize, caps: (Option<u32>, Option<u32>)) -> u16 {
+    let (set_cap, global_cap) = caps;
+    let set_room = set_cap.map_or(u32::MAX, |cap| cap.saturating_sub(get_set_slots_owned(player, set_id)));
+    let global_room = global_cap.map_or(u32::MAX, |cap| cap.saturating_sub(player.total_slots_owned as u32));
+    set_room.min(global_room).min(u16::MAX as u32) as u16
+}
+
+/// Price of attempting to steal `slots`: `steal_cost_percent_bps` of the
+/// property price per slot
+fn get_steal_cost(property: &Property, steal_cost_percent_bps: u16, slots: u16) -> Result<u64> {
+    property.price
+        .checked_mul(steal_cost_percent_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_mul(slots as u64)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Shield price for `slots` over `hours`: a share of the slots' daily income,
+/// prorated by the hour
+fn get_shield_cost(property: &Property, slots: u16, hours: u16) -> Result<u64> {
+    daily_income_per_slot(property)?
+        .checked_mul(property.shield_cost_percent_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_mul(hours as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(24)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_mul(slots as u64)
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// A new shield starts once any steal protection on the property runs out
+fn get_shield_start(player: &PlayerAccount, property_id: usize, now: i64) -> i64 {
+    player.property_steal_protection_expiry[property_id].max(now)
+}
+
+/// Returns what selling `slots` pays, the sell rate in bps and whole days held
+fn get_sell_proceeds(
+    game_config: &GameConfig,
+    property: &Property,
+    player: &PlayerAccount,
+    slots: u16,
+    now: i64,
+) -> Result<(u64, u16, i64)> {
+    let property_id = property.property_id as usize;
+    let days_held = now
+        .checked_sub(player.property_purchase_timestamp[property_id])
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(86400)
+        .ok_or(ErrorCode::Overflow)?;
+    let sell_value_bps = get_sell_value_bps(
+        game_config,
+        property,
+        player.property_purchase_timestamp[property_id],
+        now,
+    )?;
+
+    // Sold slots return to the top of the curve
+    let slots_sold = get_slots_sold(property)?;
+    let total_value = get_curve_cost(
+        property,
+        slots_sold.checked_sub(slots).ok_or(ErrorCode::Overflow)?,
+        slots,
+    )?;
+    let received = total_value
+        .checked_mul(sell_value_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow)?;
+
+    Ok((received, sell_value_bps, days_held))
+}
+
+/// Steal cooldown for a property: the property override if set, otherwise
+/// `steal_cooldown_bps` of the property's purchase cooldown.
+fn get_steal_cooldown(game_config: &GameConfig, property: &Property) -> Result<i64> {
+    if property.steal_cooldown_seconds > 0 {
+        return Ok(property.steal_cooldown_seconds);
+    }
+    property.cooldown_seconds
+        .checked_mul(game_config.steal_cooldown_bps as i64)
+        .and_then(|c| c.checked_div(10000))
+        .ok_or(ErrorCode::Overflow.into())
+}
+
+/// Sell refund in bps for slots held since `acquired_at`, ramping from the
+/// base to the max rate per whole day held. Property curve first.
+fn get_sell_value_bps(
+    game_config: &GameConfig,
+    property: &Property,
+    acquired_at: i64,
+    now: i64,
+) -> Result<u16> {
+    let (base_bps, max_bps, ramp_days) = if property.sell_max_bps > 0 {
+        (property.sell_base_bps, property.sell_max_bps, property.sell_ramp_days)
+    } else {
+        (game_config.sell_base_bps, game_config.sell_max_bps, game_config.sell_ramp_days)
+    };
+
+    let days_held = now
+        .checked_sub(acquired_at)
+        .ok_or(ErrorCode::Overflow)?
+        .max(0)
+        / 86400;
+    if ramp_days == 0 || days_held >= ramp_days as i64 {
+        return Ok(max_bps);
+    }
+
+    let additional = ((max_bps - base_bps) as u64)
+        .checked_mul(days_held as u64)
+        .ok_or(ErrorCode::Overflow)?
+        / ramp_days as u64;
+    Ok(base_bps + additional as u16)
+}
+
+fn validate_sell_curve(sell_base_bps: u16, sell_max_bps: u16, sell_ramp_days: u16) -> Result<()> {
+    require!(sell_max_bps <= 10000, ErrorCode::InvalidSellCurve);
+    require!(sell_base_bps <= sell_max_bps, ErrorCode::InvalidSellCurve);
+    require!(sell_ramp_days <= MAX_SELL_RAMP_DAYS, ErrorCode::InvalidSellCurve);
+    Ok(())
+}
+
+/// Folds newly acquired slots into the weighted-average acquisition time,
+/// so topping up an old position doesn't inherit its age.
+fn record_acquisition(player: &mut PlayerAccount, property_id: usize, slots: u16, now: i64) -> Result<()> {
+    let held = player.property_slots[property_id] as i128;
+    let added = slots as i128;
+    let total = held.checked_add(added).ok_or(ErrorCode::Overflow)?;
+    if total == 0 {
+        return Ok(());
+    }
+
+    let weighted = (player.property_purchase_timestamp[property_id] as i128)
+        .checked_mul(held)
+        .and_then(|w| w.checked_add((now as i128).checked_mul(added)?))
+        .ok_or(ErrorCode::Overflow)?;
+    player.property_purchase_timestamp[property_id] = (weighted / total) as i64;
+    Ok(())
+}
+
+/// Protection granted to a steal target, per-property override first.
+fn get_steal_protection(game_config: &GameConfig, property: &Property, success: bool) -> i64 {
+    let (property_override, global) = if success {
+        (property.steal_protection_success_seconds, game_config.steal_protection_success_seconds)
+    } else {
+        (property.steal_protection_failed_seconds, game_config.steal_protection_failed_seconds)
+    };
+    if property_override > 0 { property_override } else { global }
+}
+
+fn require_no_steal_protection(target: &PlayerAccount, property_id: usize, now: i64) -> Result<()> {
+    let remaining = target.property_steal_protection_expiry[property_id].saturating_sub(now);
+    if remaining > 0 {
+        msg!("Steal protection active: {} seconds remaining", remaining);
+        return err!(ErrorCode::StealProtectionActive);
+    }
+    Ok(())
+}
+
+/// Credits the victim's insurance payout for stolen slots still covered by
+/// an active policy. Covered slots are used up as they are paid out.
+fn apply_steal_insurance(
+    victim: &mut PlayerAccount,
+    property: &Property,
+    slots_stolen: u16,
+    coverage_bps: u16,
+    now: i64,
+    game_config: &mut GameConfig,
+) -> Result<()> {
+    let property_id = property.property_id as usize;
+    if now >= victim.property_insurance_expiry[property_id] || coverage_bps == 0 {
+        return Ok(());
+    }
+
+    let covered_slots = slots_stolen.min(victim.property_insured_slots[property_id]);
+    if covered_slots == 0 {
+        return Ok(());
+    }
+
+    let payout = property.price
+        .checked_mul(coverage_bps as u64)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_mul(covered_slots as u64)
+        .ok_or(ErrorCode::Overflow)?;
+
+    victim.property_insured_slots[property_id] -= covered_slots;
+    victim.insurance_payout_pending = victim.insurance_payout_pending
+        .checked_add(payout)
+        .ok_or(ErrorCode::Overflow)?;
+    game_config.total_insurance_liability = game_config.total_insurance_liability
+        .checked_add(payout)
+        .ok_or(ErrorCode::Overflow)?;
+
+    emit!(InsurancePayoutEvent {
+        header: next_event_header(game_config)?,
+        player: victim.owner,
+        property_id: property.property_id,
+        slots_covered: covered_slots,
+        payout,
+    });
+
+    Ok(())
+}
+
+/// Number of successful rolls out of `attempts`, each against `chance_bps`.
+/// The first roll uses `seed` directly so a single-slot steal matches the
+/// original one-roll behaviour; further rolls are derived with splitmix64.
+fn count_successful_rolls(seed: u64, attempts: u16, chance_bps: u16) -> u16 {
+    let mut roll = seed;
+    let mut successes = 0u16;
+    for i in 0..attempts {
+        if i > 0 {
+            roll = splitmix64(roll);
+        }
+        if roll % 10000 < chance_bps as u64 {
+            successes += 1;
+        }
+    }
+    successes
+}
+
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Moves `slots` of a property from victim to attacker, along with the
+/// matching daily income. Pending rewards must be settled on both first.
+#[inline(never)]
+fn transfer_stolen_slots(
+    attacker: &mut PlayerAccount,
+    victim: &mut PlayerAccount,
+    property: &Property,
+    game_config: &GameConfig,
+    slots: u16,
+    now: i64,
+) -> Result<()> {
+    let property_id = property.property_id as usize;
+    let set_id = property.set_id as usize;
+    let property_bit = get_property_bit_in_set(property.property_id, property.set_id);
+
+    victim.property_slots[property_id] = victim.property_slots[property_id]
+        .checked_sub(slots)
+        .ok_or(ErrorCode::Overflow)?;
+
+    if victim.property_shielded[property_id] > victim.property_slots[property_id] {
+        victim.property_shielded[property_id] = victim.property_slots[property_id];
+    }
+    if victim.property_insured_slots[property_id] > victim.property_slots[property_id] {
+        victim.property_insured_slots[property_id] = victim.property_slots[property_id];
+    }
+
+    if victim.property_slots[property_id] == 0 {
+        victim.set_properties_mask[set_id] &= !(1 << property_bit);
+        victim.properties_owned_count = victim.properties_owned_count
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+        refresh_set_completion(victim, set_id, now)?;
+    }
+
+    record_acquisition(attacker, property_id, slots, now)?;
+    if attacker.property_slots[property_id] == 0 {
+        attacker.properties_owned_count = attacker.properties_owned_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        attacker.set_properties_mask[set_id] |= 1 << property_bit;
+        refresh_set_completion(attacker, set_id, now)?;
+    }
+    attacker.property_slots[property_id] = attacker.property_slots[property_id]
+        .checked_add(slots)
+        .ok_or(ErrorCode::Overflow)?;
+
+    attacker.total_slots_owned = attacker.total_slots_owned
+        .checked_add(slots)
+        .ok_or(ErrorCode::Overflow)?;
+    victim.total_slots_owned = victim.total_slots_owned
+        .checked_sub(slots)
+        .ok_or(ErrorCode::Overflow)?;
+
+    attacker.total_base_daily_income = current_daily_income(attacker, game_config)?;
+    victim.total_base_daily_income = current_daily_income(victim, game_config)?;
+
+    Ok(())
+}
+
+fn get_property_bit_in_set(property_id: u8, set_id: u8) -> u8 {
+    match set_id {
+        0 => property_id,
+        1 => property_id.saturating_sub(2),
+        2 => property_id.saturating_sub(5),
+        3 => property_id.saturating_sub(8),
+        4 => property_id.saturating_sub(11),
+        5 => property_id.saturating_sub(14),
+        6 => property_id.saturating_sub(17),
+        7 => property_id.saturating_sub(20),
+        _ => 0,
+    }
+}
+
+fn get_set_first_property(set_id: u8) -> u8 {
+    match set_id {
+        0 => 0,
+        1 => 2,
+        2 => 5,
+        3 => 8,
+        4 => 11,
+        5 => 14,
+        6 => 17,
+        7 => 20,
+        _ => 0,
+    }
+}
+
+fn get_properties_in_set(set_id: u8) -> u8 {
+    match set_id {
+        0 | 7 => 2,
+        _ => 3,
+    }
+}
+
+fn count_properties_in_set(mask: u8) -> u8 {
+    mask.count_ones() as u8
+}
+
+fn is_set_complete(player: &PlayerAccount, set_id: u8) -> bool {
+    let required = get_properties_in_set(set_id);
+    let owned = count_properties_in_set(player.set_properties_mask[set_id as usize]);
+    owned >= required
+}
+
+/// Keeps `set_completed_at` and `complete_sets_owned` in step with the
+/// ownership mask. Call after each mask change, once rewards are settled.
+fn refresh_set_completion(player: &mut PlayerAccount, set_id: usize, now: i64) -> Result<()> {
+    let complete = is_set_complete(player, set_id as u8);
+    let was_complete = player.set_completed_at[set_id] > 0;
+
+    if complete && !was_complete {
+        player.set_completed_at[set_id] = now;
+        player.complete_sets_owned = player.complete_sets_owned
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+    } else if !complete && was_complete {
+        player.set_completed_at[set_id] = 0;
+        player.complete_sets_owned = player.complete_sets_owned
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+
+    Ok(())
+}
+
+// ========== ACCOUNT CONTEXTS ==========
+
+#[derive(Accounts)]
+pub struct InitializeGame<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<GameConfig>(),
+        seeds = [b"game_config"],
+        bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_mint,
+        token::authority = reward_pool_vault,
+        token::token_program = token_program,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint,
+        associated_token::authority = dev_wallet,
+        associated_token::token_program = token_program,
+    )]
+    pub dev_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Dev wallet address verified against constant
+    #[account(
+        constraint = dev_wallet.key() == DEV_WALLET.parse::<Pubkey>().unwrap() @ ErrorCode::InvalidDevWallet
+    )]
+    pub dev_wallet: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = token_mint,
+        associated_token::authority = marketing_wallet,
+        associated_token::token_program = token_program,
+    )]
+    pub marketing_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Marketing wallet address verified against constant
+    #[account(
+        constraint = marketing_wallet.key() == MARKETING_WALLET.parse::<Pubkey>().unwrap() @ ErrorCode::InvalidMarketingWallet
+    )]
+    pub marketing_wallet: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>, 
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(property_id: u8)]
+pub struct InitializeProperty<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Property::SIZE,
+        seeds = [b"property", property_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(
+        mut,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAccumulationTiers<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AccumulationTiers::SIZE,
+        seeds = [b"accumulation_tiers"],
+        bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAccumulationTiers<'info> {
+    #[account(
+        mut,
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewAccumulationBonus<'info> {
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+}
+
+#[derive(Accounts)]
+pub struct AuditPlayer<'info> {
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteClaim<'info> {
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteProperty<'info> {
+    #[account(
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePlayer<'info> {
+    #[account(
+        init,
+        payer = player,
+        space = 8 + std::mem::size_of::<PlayerAccount>(),
+        seeds = [b"player", player.key().as_ref()],
+        bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    /// Referrer's player account, required when a referrer is given
+    #[account(mut)]
+    pub referrer_account: Option<AccountLoader<'info, PlayerAccount>>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(slots: u16)]
+pub struct BuyProperty<'info> {
+    #[account(
+        mut,
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    /// CHECK: Referrer's player account, credited with the referral share.
+    /// Required when the player has a referrer; get_referral_bps checks it.
+    #[account(mut)]
+    pub referrer_account: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidDevTokenAccount
+    )]
+    pub dev_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidMarketingTokenAccount
+    )]
+    pub marketing_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        mut,
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(slots: u16)]
+pub struct BuyPropertyWithMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"payment_mint", payment_token_mint.key().as_ref()],
+        bump = payment_mint_config.bump
+    )]
+    pub payment_mint_config: Account<'info, PaymentMint>,
+
+    #[account(
+        mint::token_program = payment_token_program
+    )]
+    pub payment_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = player_payment_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_payment_account.mint == payment_token_mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"payment_vault", payment_token_mint.key().as_ref()],
+        bump = payment_mint_config.vault_bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Program of the payment mint, which may differ from the game mint's
+    pub payment_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateShield<'info> {
+    #[account(
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    /// CHECK: Referrer's player account, credited with the referral share.
+    /// Required when the player has a referrer; get_referral_bps checks it.
+    #[account(mut)]
+    pub referrer_account: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidDevTokenAccount
+    )]
+    pub dev_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidMarketingTokenAccount
+    )]
+    pub marketing_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        mut,
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct StealPropertyInstant<'info> {
+    #[account(
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(
+        mut,
+        seeds = [b"player", attacker.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    /// CHECK: Referrer's player account, credited with the referral share.
+    /// Required when the player has a referrer; get_referral_bps checks it.
+    #[account(mut)]
+    pub referrer_account: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == attacker.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidDevTokenAccount
+    )]
+    pub dev_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidMarketingTokenAccount
+    )]
+    pub marketing_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        mut,
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+    
+    /// CHECK: Slot hashes sysvar for entropy
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub attacker: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RetaliateSteal<'info> {
+    #[account(
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    /// Player who stole from us, must match the recorded retaliation target
+    #[account(mut)]
+    pub target_account: AccountLoader<'info, PlayerAccount>,
+
+    /// CHECK: Referrer's player account, credited with the referral share.
+    /// Required when the player has a referrer; get_referral_bps checks it.
+    #[account(mut)]
+    pub referrer_account: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidDevTokenAccount
+    )]
+    pub dev_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidMarketingTokenAccount
+    )]
+    pub marketing_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        mut,
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+    
+    /// CHECK: Slot hashes sysvar for entropy
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct BuyStealInsurance<'info> {
+    #[account(
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    /// CHECK: Referrer's player account, credited with the referral share.
+    /// Required when the player has a referrer; get_referral_bps checks it.
+    #[account(mut)]
+    pub referrer_account: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidDevTokenAccount
+    )]
+    pub dev_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint, token_program.key()) @ ErrorCode::InvalidMarketingTokenAccount
+    )]
+    pub marketing_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        mut,
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+    
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CrankPlayer<'info> {
+    #[account(mut)]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        constraint = cranker_token_account.owner == cranker.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = cranker_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub cranker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    pub cranker: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardsVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + VestingAccount::SIZE,
+        seeds = [b"vesting", player.key().as_ref()],
         bump
     )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    #[account(
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"vesting", player.key().as_ref()],
+        bump = vesting_account.bump
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
-    pub token_mint: Account<'info, Mint>,
-    
+
     #[account(
-        init,
-        payer = authority,
-        token::mint = token_mint,
-        token::authority = reward_pool_vault,
-        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
-        bump
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
     #[account(
-        init_if_needed,
-        payer = authority,
-        associated_token::mint = token_mint,
-        associated_token::authority = dev_wallet,
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
     )]
-    pub dev_token_account: Account<'info, TokenAccount>,
+    pub player_account: AccountLoader<'info, PlayerAccount>,
     
-    /// CHECK: Dev wallet address verified against constant
     #[account(
-        constraint = dev_wallet.key() == DEV_WALLET.parse::<Pubkey>().unwrap() @ ErrorCode::InvalidDevWallet
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub dev_wallet: UncheckedAccount<'info>,
-
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
     #[account(
-        init_if_needed,
-        payer = authority,
-        associated_token::mint = token_mint,
-        associated_token::authority = marketing_wallet,
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
     )]
-    pub marketing_token_account: Account<'info, TokenAccount>,
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
     
-    /// CHECK: Marketing wallet address verified against constant
     #[account(
-        constraint = marketing_wallet.key() == MARKETING_WALLET.parse::<Pubkey>().unwrap() @ ErrorCode::InvalidMarketingWallet
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
     )]
-    pub marketing_wallet: UncheckedAccount<'info>,
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub associated_token_program: Program<'info, AssociatedToken>, 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    pub player: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-#[instruction(property_id: u8)]
-pub struct InitializeProperty<'info> {
+pub struct ClaimInsurancePayout<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Property::SIZE,
-        seeds = [b"property", property_id.to_le_bytes().as_ref()],
-        bump
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
     )]
-    pub property: Account<'info, Property>,
+    pub player_account: AccountLoader<'info, PlayerAccount>,
     
     #[account(
         mut,
-        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub authority: Signer<'info>,
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
+        mut,
         seeds = [b"game_config"],
         bump = game_config.load()?.bump
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
-    pub system_program: Program<'info, System>,
-}
 
-#[derive(Accounts)]
-pub struct InitializePlayer<'info> {
     #[account(
-        init,
-        payer = player,
-        space = 8 + std::mem::size_of::<PlayerAccount>(),
-        seeds = [b"player", player.key().as_ref()],
-        bump
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
     pub player: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-#[instruction(slots: u16)]
-pub struct BuyProperty<'info> {
+pub struct SellProperty<'info> {
     #[account(
         mut,
         seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
@@ -1498,249 +5824,403 @@ pub struct BuyProperty<'info> {
         constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
         constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
         seeds = [b"reward_pool_vault", game_config.key().as_ref()],
         bump = game_config.load()?.reward_pool_vault_bump
     )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
-        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint) @ ErrorCode::InvalidDevTokenAccount
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
     )]
-    pub dev_token_account: Account<'info, TokenAccount>,
+    pub game_config: AccountLoader<'info, GameConfig>,
 
     #[account(
-        mut,
-        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint) @ ErrorCode::InvalidMarketingTokenAccount
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub marketing_token_account: Account<'info, TokenAccount>,
-    
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
-        seeds = [b"game_config"],
-        bump = game_config.load()?.bump
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
     )]
-    pub game_config: AccountLoader<'info, GameConfig>,
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
     
     #[account(mut)]
     pub player: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct ActivateShield<'info> {
+#[instruction(property_id: u8)]
+pub struct AdminUpdateProperty<'info> {
     #[account(
-        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"property", property_id.to_le_bytes().as_ref()],
         bump = property.bump
     )]
     pub property: Account<'info, Property>,
     
     #[account(
         mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminUpdateGame<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePlayerAccount<'info> {
+    #[account(
+        mut,
+        close = rent_receiver,
         seeds = [b"player", player.key().as_ref()],
         bump = player_account.load()?.bump
     )]
     pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+
     #[account(
         mut,
-        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
-        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    #[account(mut)]
+    pub player: Signer<'info>,
+    
+    /// CHECK: Receives the rent refund
+    #[account(mut)]
+    pub rent_receiver: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminClosePlayerAccount<'info> {
+    /// CHECK: Force closing account - verified by owner check
+    #[account(mut)]
+    pub player_account: AccountInfo<'info>,
     
     #[account(
         mut,
-        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
-        bump = game_config.load()?.reward_pool_vault_bump
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
     
+    /// CHECK: Receives the rent refund
+    #[account(mut)]
+    pub rent_receiver: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_player: Pubkey)]
+pub struct AdminGrantProperty<'info> {
     #[account(
         mut,
-        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint) @ ErrorCode::InvalidDevTokenAccount
+        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
     )]
-    pub dev_token_account: Account<'info, TokenAccount>,
-
+    pub property: Account<'info, Property>,
+    
     #[account(
         mut,
-        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint) @ ErrorCode::InvalidMarketingTokenAccount
+        seeds = [b"player", target_player.as_ref()],
+        bump = player_account.load()?.bump
     )]
-    pub marketing_token_account: Account<'info, TokenAccount>,
+    pub player_account: AccountLoader<'info, PlayerAccount>,
     
     #[account(
+        mut,
         seeds = [b"game_config"],
-        bump = game_config.load()?.bump
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
     
     #[account(mut)]
-    pub player: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct StealPropertyInstant<'info> {
+#[instruction(slots: u16)]
+pub struct AdminRevokeProperty<'info> {
     #[account(
+        mut,
         seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
         bump = property.bump
     )]
     pub property: Account<'info, Property>,
     
+    #[account(mut)]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+    
     #[account(
         mut,
-        seeds = [b"player", attacker.key().as_ref()],
-        bump = player_account.load()?.bump
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
     )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminClearCooldown<'info> {
+    #[account(mut)]
     pub player_account: AccountLoader<'info, PlayerAccount>,
     
     #[account(
         mut,
-        constraint = player_token_account.owner == attacker.key() @ ErrorCode::InvalidTokenAccountOwner,
-        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminClearStealCooldown<'info> {
+    #[account(mut)]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminGrantShield<'info> {
+    #[account(mut)]
+    pub player_account: AccountLoader<'info, PlayerAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub game_config: AccountLoader<'info, GameConfig>,
     
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminEmergencyWithdraw<'info> {
     #[account(
         mut,
         seeds = [b"reward_pool_vault", game_config.key().as_ref()],
         bump = game_config.load()?.reward_pool_vault_bump
     )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub destination_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
-        constraint = dev_token_account.key() == get_ata(game_config.load()?.dev_wallet, game_config.load()?.token_mint) @ ErrorCode::InvalidDevTokenAccount
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
-    pub dev_token_account: Account<'info, TokenAccount>,
+    pub game_config: AccountLoader<'info, GameConfig>,
 
     #[account(
-        mut,
-        constraint = marketing_token_account.key() == get_ata(game_config.load()?.marketing_wallet, game_config.load()?.token_mint) @ ErrorCode::InvalidMarketingTokenAccount
-    )]
-    pub marketing_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        seeds = [b"game_config"],
-        bump = game_config.load()?.bump
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub game_config: AccountLoader<'info, GameConfig>,
-    
-    /// CHECK: Slot hashes sysvar for entropy
-    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
-    pub slot_hashes: AccountInfo<'info>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
-    #[account(mut)]
-    pub attacker: Signer<'info>,
+    pub authority: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimRewards<'info> {
+pub struct AddPaymentMint<'info> {
     #[account(
-        mut,
-        seeds = [b"player", player.key().as_ref()],
-        bump = player_account.load()?.bump
+        init,
+        payer = authority,
+        space = 8 + PaymentMint::SIZE,
+        seeds = [b"payment_mint", payment_token_mint.key().as_ref()],
+        bump
     )]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+    pub payment_mint_config: Account<'info, PaymentMint>,
+
+    pub payment_token_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
-        mut,
-        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
-        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+        init,
+        payer = authority,
+        token::mint = payment_token_mint,
+        token::authority = payment_vault,
+        token::token_program = token_program,
+        seeds = [b"payment_vault", payment_token_mint.key().as_ref()],
+        bump
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
-    
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+
     #[account(
         mut,
-        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
-        bump = game_config.load()?.reward_pool_vault_bump
-    )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
-    
-    #[account(
         seeds = [b"game_config"],
-        bump = game_config.load()?.bump
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
+
     #[account(mut)]
-    pub player: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SellProperty<'info> {
+pub struct UpdatePaymentMint<'info> {
     #[account(
         mut,
-        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
-        bump = property.bump
+        seeds = [b"payment_mint", payment_mint_config.mint.as_ref()],
+        bump = payment_mint_config.bump
     )]
-    pub property: Account<'info, Property>,
-    
+    pub payment_mint_config: Account<'info, PaymentMint>,
+
     #[account(
         mut,
-        seeds = [b"player", player.key().as_ref()],
-        bump = player_account.load()?.bump
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+    pub game_config: AccountLoader<'info, GameConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostPaymentRate<'info> {
     #[account(
         mut,
-        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
-        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+        seeds = [b"payment_mint", payment_mint_config.mint.as_ref()],
+        bump = payment_mint_config.bump
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
-    
+    pub payment_mint_config: Account<'info, PaymentMint>,
+
     #[account(
         mut,
-        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
-        bump = game_config.load()?.reward_pool_vault_bump
-    )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
-    
-    #[account(
         seeds = [b"game_config"],
-        bump = game_config.load()?.bump
+        bump = game_config.load()?.bump,
+        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
-    #[account(mut)]
-    pub player: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(property_id: u8)]
-pub struct AdminUpdateProperty<'info> {
+pub struct WithdrawPaymentVault<'info> {
+    #[account(
+        seeds = [b"payment_mint", payment_token_mint.key().as_ref()],
+        bump = payment_mint_config.bump
+    )]
+    pub payment_mint_config: Account<'info, PaymentMint>,
+
+    pub payment_token_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
-        seeds = [b"property", property_id.to_le_bytes().as_ref()],
-        bump = property.bump
+        seeds = [b"payment_vault", payment_token_mint.key().as_ref()],
+        bump = payment_mint_config.vault_bump
     )]
-    pub property: Account<'info, Property>,
-    
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination_account: InterfaceAccount<'info, TokenAccount>,
+
     #[account(
+        mut,
         seeds = [b"game_config"],
         bump = game_config.load()?.bump,
         constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
+
     pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct AdminUpdateGame<'info> {
+pub struct SettlePaymentLiability<'info> {
+    #[account(
+        mut,
+        seeds = [b"payment_mint", payment_mint_config.mint.as_ref()],
+        bump = payment_mint_config.bump
+    )]
+    pub payment_mint_config: Account<'info, PaymentMint>,
+
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = authority_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
+    )]
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
         seeds = [b"game_config"],
@@ -1748,106 +6228,100 @@ pub struct AdminUpdateGame<'info> {
         constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
+
     pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct ClosePlayerAccount<'info> {
+pub struct ReportRewardPoolBalances<'info> {
     #[account(
-        mut,
-        close = rent_receiver,
-        seeds = [b"player", player.key().as_ref()],
-        bump = player_account.load()?.bump
+        seeds = [b"reward_pool_vault", game_config.key().as_ref()],
+        bump = game_config.load()?.reward_pool_vault_bump
     )]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
-    
-    #[account(mut)]
-    pub player: Signer<'info>,
-    
-    /// CHECK: Receives the rent refund
-    #[account(mut)]
-    pub rent_receiver: AccountInfo<'info>,
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"game_config"],
+        bump = game_config.load()?.bump
+    )]
+    pub game_config: AccountLoader<'info, GameConfig>,
 }
 
 #[derive(Accounts)]
-pub struct AdminClosePlayerAccount<'info> {
-    /// CHECK: Force closing account - verified by owner check
-    #[account(mut)]
-    pub player_account: AccountInfo<'info>,
-    
+pub struct MigrateGameConfig<'info> {
+    /// CHECK: Legacy-layout game config; the instruction checks its
+    /// discriminator, length and authority before rewriting it
     #[account(
+        mut,
         seeds = [b"game_config"],
-        bump = game_config.load()?.bump,
-        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+        bump,
+        owner = crate::ID
     )]
-    pub game_config: AccountLoader<'info, GameConfig>,
-    
+    pub game_config: UncheckedAccount<'info>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
-    
-    /// CHECK: Receives the rent refund
-    #[account(mut)]
-    pub rent_receiver: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(target_player: Pubkey)]
-pub struct AdminGrantProperty<'info> {
+#[instruction(property_id: u8)]
+pub struct MigrateProperty<'info> {
+    /// CHECK: Legacy-layout property; the instruction checks its
+    /// discriminator and length before growing it
     #[account(
         mut,
-        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
-        bump = property.bump
+        seeds = [b"property", property_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
     )]
-    pub property: Account<'info, Property>,
-    
+    pub property: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [b"player", target_player.as_ref()],
-        bump = player_account.load()?.bump
-    )]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
-    
-    #[account(
         seeds = [b"game_config"],
         bump = game_config.load()?.bump,
         constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
+
     #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(slots: u16)]
-pub struct AdminRevokeProperty<'info> {
+pub struct MigratePlayer<'info> {
+    /// CHECK: Legacy-layout player account; the instruction checks its
+    /// discriminator and length before rewriting it
     #[account(
         mut,
-        seeds = [b"property", property.property_id.to_le_bytes().as_ref()],
-        bump = property.bump
+        owner = crate::ID
     )]
-    pub property: Account<'info, Property>,
-    
-    #[account(mut)]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+    pub player_account: UncheckedAccount<'info>,
+
     #[account(
+        mut,
         seeds = [b"game_config"],
         bump = game_config.load()?.bump,
         constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
+
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminClearCooldown<'info> {
-    #[account(mut)]
-    pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+pub struct AdminTransferAuthority<'info> {
     #[account(
+        mut,
         seeds = [b"game_config"],
         bump = game_config.load()?.bump,
         constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
@@ -1858,76 +6332,132 @@ pub struct AdminClearCooldown<'info> {
 }
 
 #[derive(Accounts)]
-pub struct AdminClearStealCooldown<'info> {
-    #[account(mut)]
+pub struct StakeTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
     pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + StakePosition::SIZE,
+        seeds = [b"stake", player.key().as_ref()],
+        bump
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        token::mint = token_mint,
+        token::authority = stake_vault,
+        token::token_program = token_program,
+        seeds = [b"stake_vault", game_config.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        constraint = token_mint.key() == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
     #[account(
+        mut,
         seeds = [b"game_config"],
-        bump = game_config.load()?.bump,
-        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+        bump = game_config.load()?.bump
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
-    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminGrantShield<'info> {
-    #[account(mut)]
+pub struct UnstakeTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"player", player.key().as_ref()],
+        bump = player_account.load()?.bump
+    )]
     pub player_account: AccountLoader<'info, PlayerAccount>,
-    
+
     #[account(
-        seeds = [b"game_config"],
-        bump = game_config.load()?.bump,
-        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+        mut,
+        close = player,
+        seeds = [b"stake", player.key().as_ref()],
+        bump = stake_position.bump
     )]
-    pub game_config: AccountLoader<'info, GameConfig>,
-    
-    pub authority: Signer<'info>,
-}
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault", game_config.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ ErrorCode::InvalidTokenAccountOwner,
+        constraint = player_token_account.mint == game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
 
-#[derive(Accounts)]
-pub struct AdminEmergencyWithdraw<'info> {
     #[account(
         mut,
         seeds = [b"reward_pool_vault", game_config.key().as_ref()],
         bump = game_config.load()?.reward_pool_vault_bump
     )]
-    pub reward_pool_vault: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub destination_account: Account<'info, TokenAccount>,
-    
+    pub reward_pool_vault: InterfaceAccount<'info, TokenAccount>,
+
     #[account(
+        mut,
         seeds = [b"game_config"],
-        bump = game_config.load()?.bump,
-        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+        bump = game_config.load()?.bump
     )]
     pub game_config: AccountLoader<'info, GameConfig>,
-    
-    pub authority: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-}
 
-#[derive(Accounts)]
-pub struct AdminTransferAuthority<'info> {
     #[account(
-        mut,
-        seeds = [b"game_config"],
-        bump = game_config.load()?.bump,
-        constraint = game_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+        address = game_config.load()?.token_mint @ ErrorCode::InvalidTokenMint
     )]
-    pub game_config: AccountLoader<'info, GameConfig>,
-    
-    pub authority: Signer<'info>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"accumulation_tiers"],
+        bump = accumulation_tiers.bump
+    )]
+    pub accumulation_tiers: Account<'info, AccumulationTiers>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 // ========== HELPER FUNCTION FOR ATA DERIVATION ==========
 
-fn get_ata(wallet: Pubkey, mint: Pubkey) -> Pubkey {
-    anchor_spl::associated_token::get_associated_token_address(&wallet, &mint)
+fn get_ata(wallet: Pubkey, mint: Pubkey, token_program: Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address_with_program_id(&wallet, &mint, &token_program)
 }
 
 // ========== STATE ACCOUNTS ==========
@@ -1940,7 +6470,10 @@ pub struct GameConfig {
     pub marketing_wallet: Pubkey,
     pub token_mint: Pubkey,
     pub reward_pool_vault: Pubkey,
+    pub allowlist_root: [u8; 32],
     
+    /// Legacy tier fields, moved into `AccumulationTiers` by
+    /// `initialize_accumulation_tiers`
     pub accumulation_tier1_threshold: u64,
     pub accumulation_tier2_threshold: u64,
     pub accumulation_tier3_threshold: u64,
@@ -1949,11 +6482,60 @@ pub struct GameConfig {
     pub accumulation_tier6_threshold: u64,
     pub accumulation_tier7_threshold: u64,
     pub accumulation_tier8_threshold: u64,
+    pub retaliation_window_seconds: i64,
+    pub steal_protection_success_seconds: i64,
+    pub steal_protection_failed_seconds: i64,
+    pub property_purchase_cooldown_seconds: i64,
+    pub purchase_window_seconds: i64,
+    pub early_access_end: i64,
+    pub stake_boost_unit: u64,
+    pub vesting_period_seconds: i64,
+    /// Rewards sitting in vesting schedules, reserved in `reward_pool_vault`
+    pub total_vesting_liability: u64,
+    /// Referral rebates credited but not yet claimed, also reserved
+    pub total_referral_liability: u64,
+    /// Insurance payouts credited but not yet claimed, also reserved
+    pub total_insurance_liability: u64,
+    pub total_burned: u64,
+    /// Sequence number of the last event emitted
+    pub event_sequence: u64,
+    /// Per-slot income each property has paid since initialization, scaled
+    /// by INCOME_INDEX_SCALE, as of `property_income_updated_at`
+    pub property_income_index: [u64; MAX_PROPERTIES],
+    /// Daily income per slot each property currently pays
+    pub property_income_rate: [u64; MAX_PROPERTIES],
+    pub property_income_updated_at: [i64; MAX_PROPERTIES],
+    /// Paid to whoever cranks a stale player account, out of the budget below
+    pub crank_tip_amount: u64,
+    pub crank_tip_budget: u64,
+    pub crank_min_interval_seconds: i64,
+
+    pub set_total_supply: [u32; 8],
+    pub total_supply: u32,
     
     pub set_bonus_bps: [u16; 8],
     
     pub steal_chance_bps: u16,
     pub steal_cost_percent_bps: u16,
+    pub referral_bps: u16,
+    pub max_steal_slots: u16,
+    pub retaliation_cost_bps: u16,
+    pub insurance_premium_bps: u16,
+    pub insurance_coverage_bps: u16,
+    pub steal_cooldown_bps: u16,
+    pub purchase_window_max_slots: u16,
+    pub max_set_share_bps: u16,
+    pub max_global_share_bps: u16,
+    pub sell_base_bps: u16,
+    pub sell_max_bps: u16,
+    pub sell_ramp_days: u16,
+    pub stake_boost_bps_per_unit_day: u16,
+    pub stake_max_boost_bps: u16,
+    pub stake_early_unlock_penalty_bps: u16,
+    pub stake_max_lock_days: u16,
+    pub vesting_bonus_bps: u16,
+    pub burn_bps: u16,
+    /// Legacy, see `accumulation_tier1_threshold`
     pub accumulation_tier1_bonus_bps: u16,
     pub accumulation_tier2_bonus_bps: u16,
     pub accumulation_tier3_bonus_bps: u16,
@@ -1963,82 +6545,475 @@ pub struct GameConfig {
     pub accumulation_tier7_bonus_bps: u16,
     pub accumulation_tier8_bonus_bps: u16,
     
+    pub game_paused: u8,
+    pub steal_mode: u8,
+    pub purchase_limit_flags: u8,
+    pub bump: u8,
+    pub reward_pool_vault_bump: u8,
+    pub _padding: [u8; 7],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AccumulationTier {
+    pub threshold: u64,
+    pub bonus_bps: u16,
+}
+
+#[account]
+pub struct AccumulationTiers {
+    pub entries: Vec<AccumulationTier>,
+    pub bump: u8,
+}
+
+impl AccumulationTiers {
+    pub const SIZE: usize = 4 + MAX_ACCUMULATION_TIERS * 10 + 1;
+}
+
+#[account]
+pub struct Property {
+    pub property_id: u8,
+    pub set_id: u8,
+    pub max_slots_per_property: u16,
+    pub available_slots: u16,
+    pub max_per_player: u16,
+    pub price: u64,
+    pub yield_percent_bps: u16,
+    pub shield_cost_percent_bps: u16,
+    pub cooldown_seconds: i64,
+    pub bump: u8,
+
+    // Overrides of the GameConfig steal settings, 0 = use global
+    pub steal_protection_success_seconds: i64,
+    pub steal_protection_failed_seconds: i64,
+    pub steal_cooldown_seconds: i64,
+
+    pub pricing_mode: u8,
+    pub curve_param: u64,
+
+    pub launch_start_time: i64,
+    pub launch_end_time: i64,
+    pub launch_start_price: u64,
+    pub launch_wallet_cap: u16,
+    pub launch_stage: u8,
+    pub launch_merkle_root: [u8; 32],
+
+    pub sell_base_bps: u16,
+    pub sell_max_bps: u16,
+    pub sell_ramp_days: u16,
+    
+    pub padding: [u8; 25],
+}
+
+impl Property {
+    pub const SIZE: usize = 127 + 25;
+}
+
+#[account(zero_copy)]
+#[repr(C)]
+pub struct PlayerAccount {
+    pub owner: Pubkey,
+    pub referrer: Pubkey,
+    pub property_retaliation_target: [Pubkey; MAX_PROPERTIES],
+    
+    pub total_base_daily_income: u64,
+    pub last_accumulation_timestamp: i64,
+    pub total_rewards_claimed: u64,
+    pub pending_rewards: u64,
+    pub pending_bonus_rewards: u64,
+    pub referral_rewards_pending: u64,
+    pub total_referral_earnings: u64,
+    pub insurance_payout_pending: u64,
+    pub purchase_window_start: i64,
+    pub last_claim_timestamp: i64,
+    /// When the stake behind `stake_boost_bps` unlocks; the boost stops there
+    pub stake_unlock_at: i64,
+    
+    pub total_steals_attempted: u32,
+    pub total_steals_successful: u32,
+    pub referral_count: u32,
+    
+    pub total_slots_owned: u16,
+    pub purchase_window_slots: u16,
+    pub purchase_window_prev_slots: u16,
+    pub allowlist_slots_used: u16,
+    /// Income boost from the player's stake position while it is locked
+    pub stake_boost_bps: u16,
+    
+    pub complete_sets_owned: u8,
+    pub properties_owned_count: u8,
+    pub bump: u8,
+    pub _padding1: [u8; 7],
+    
+    /// Weighted-average acquisition time of the slots currently held
+    pub property_purchase_timestamp: [i64; MAX_PROPERTIES],
+    pub property_shield_expiry: [i64; MAX_PROPERTIES],
+    pub property_shield_cooldown: [i64; MAX_PROPERTIES],
+    pub property_steal_protection_expiry: [i64; MAX_PROPERTIES],
+    pub set_cooldown_timestamp: [i64; MAX_SETS],
+    pub set_cooldown_duration: [i64; MAX_SETS],
+    pub set_completed_at: [i64; MAX_SETS],
+    pub set_bonus_accrued: [u64; MAX_SETS],
+    pub steal_cooldown_timestamp: [i64; MAX_PROPERTIES],
+    pub property_retaliation_expiry: [i64; MAX_PROPERTIES],
+    pub property_insurance_expiry: [i64; MAX_PROPERTIES],
+    pub property_last_purchase_timestamp: [i64; MAX_PROPERTIES],
+    /// Property income indexes as of the player's last settlement
+    pub property_income_index: [u64; MAX_PROPERTIES],
+    
+    pub property_slots: [u16; MAX_PROPERTIES],
+    pub property_shielded: [u16; MAX_PROPERTIES],
+    pub property_insured_slots: [u16; MAX_PROPERTIES],
+    
+    pub set_last_purchased_property: [u8; MAX_SETS],
+    pub set_properties_mask: [u8; MAX_SETS],
+    pub _padding2: [u8; 4],
+}
+
+/// `GameConfig` as first deployed, read by `migrate_game_config`
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct LegacyGameConfig {
+    pub authority: Pubkey,
+    pub dev_wallet: Pubkey,
+    pub marketing_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    pub reward_pool_vault: Pubkey,
+    pub accumulation_tier_thresholds: [u64; 8],
+    pub set_bonus_bps: [u16; 8],
+    pub steal_chance_bps: u16,
+    pub steal_cost_percent_bps: u16,
+    pub accumulation_tier_bonus_bps: [u16; 8],
     pub game_paused: u8,
     pub bump: u8,
     pub reward_pool_vault_bump: u8,
     pub _padding: [u8; 9],
 }
 
-#[account]
-pub struct Property {
-    pub property_id: u8,
+/// `PlayerAccount` as first deployed, read by `migrate_player`
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct LegacyPlayerAccount {
+    pub owner: Pubkey,
+    pub total_base_daily_income: u64,
+    pub last_accumulation_timestamp: i64,
+    pub total_rewards_claimed: u64,
+    pub pending_rewards: u64,
+    pub total_steals_attempted: u32,
+    pub total_steals_successful: u32,
+    pub total_slots_owned: u16,
+    pub complete_sets_owned: u8,
+    pub properties_owned_count: u8,
+    pub bump: u8,
+    pub _padding1: [u8; 3],
+    pub property_purchase_timestamp: [i64; MAX_PROPERTIES],
+    pub property_shield_expiry: [i64; MAX_PROPERTIES],
+    pub property_shield_cooldown: [i64; MAX_PROPERTIES],
+    pub property_steal_protection_expiry: [i64; MAX_PROPERTIES],
+    pub set_cooldown_timestamp: [i64; MAX_SETS],
+    pub set_cooldown_duration: [i64; MAX_SETS],
+    pub steal_cooldown_timestamp: [i64; MAX_PROPERTIES],
+    pub property_slots: [u16; MAX_PROPERTIES],
+    pub property_shielded: [u16; MAX_PROPERTIES],
+    pub set_last_purchased_property: [u8; MAX_SETS],
+    pub set_properties_mask: [u8; MAX_SETS],
+}
+
+#[account]
+pub struct StakePosition {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub boost_bps: u16,
+    pub lock_days: u16,
+    pub staked_at: i64,
+    pub unlock_at: i64,
+    pub bump: u8,
+    pub padding: [u8; 32],
+}
+
+impl StakePosition {
+    pub const SIZE: usize = 61 + 32;
+}
+
+#[account]
+pub struct VestingAccount {
+    pub owner: Pubkey,
+    /// Still vesting, across every tranche
+    pub locked_amount: u64,
+    /// Vested and ready for `withdraw_vested`
+    pub released_amount: u64,
+    pub total_withdrawn: u64,
+    /// Last settlement; each tranche vests linearly from here to its end
+    pub start_time: i64,
+    /// End of the oldest tranche, which holds what `tranches` doesn't
+    pub end_time: i64,
+    pub bump: u8,
+    /// Newer claims still vesting, in place of the former padding
+    pub tranches: [VestingTranche; MAX_VESTING_TRANCHES - 1],
+}
+
+impl VestingAccount {
+    pub const SIZE: usize = 73 + 32;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct VestingTranche {
+    pub amount: u64,
+    pub end_time: i64,
+}
+
+/// Alternate payment mint with an admin-posted, oracle-format rate
+#[account]
+pub struct PaymentMint {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    /// Whole payment tokens per whole game token, as `price * 10^expo`
+    pub price: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub max_staleness_seconds: i64,
+    pub total_received: u64,
+    pub enabled: u8,
+    pub bump: u8,
+    pub vault_bump: u8,
+    /// Game tokens the reward pool is owed for purchases paid in this mint
+    pub reward_pool_owed: u64,
+    pub padding: [u8; 24],
+}
+
+impl PaymentMint {
+    pub const SIZE: usize = 111 + 24;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct MintBalance {
+    pub mint: Pubkey,
+    pub balance: u64,
+}
+
+/// Result of `audit_player`. An empty `violations` list means every cached
+/// total matches the per-property holdings.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlayerAudit {
+    pub player: Pubkey,
+    /// False when some held property wasn't supplied, so income wasn't checked
+    pub income_checked: bool,
+    pub violations: Vec<AuditViolation>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AuditViolation {
+    TotalSlotsMismatch { stored: u16, computed: u32 },
+    OwnedCountMismatch { stored: u8, computed: u8 },
+    SetMaskMismatch { set_id: u8, stored: u8, computed: u8 },
+    /// `set_completed_at` disagrees with whether the set is held
+    SetCompletionStale { set_id: u8 },
+    CompleteSetsMismatch { stored: u8, computed: u8 },
+    IncomeMismatch { stored: u64, computed: u64 },
+    ShieldedExceedsSlots { property_id: u8 },
+    InsuredExceedsSlots { property_id: u8 },
+}
+
+/// Result of `quote_claim`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ClaimQuote {
+    pub pending_rewards: u64,
+    /// Set and accumulation bonuses
+    pub pending_bonus_rewards: u64,
+    pub total: u64,
+    pub daily_income: u64,
+}
+
+/// Result of `quote_buy`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BuyQuote {
+    pub slots: u16,
+    pub total_cost: u64,
+    pub daily_income_increase: u64,
+}
+
+/// Result of `quote_shield`. The shield covers every held slot and starts
+/// once any steal protection runs out.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ShieldQuote {
+    pub slots: u16,
+    pub cost: u64,
+    pub starts_at: i64,
+    pub expires_at: i64,
+}
+
+/// Result of `quote_steal`. Each attempted slot succeeds independently with
+/// `chance_bps`; the target's unshielded slots cap the result further.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct StealQuote {
+    pub slots: u16,
+    pub cost: u64,
+    pub chance_bps: u16,
+    pub max_slots_gained: u16,
+}
+
+/// Result of `quote_sell`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SellQuote {
+    pub slots: u16,
+    pub sell_value_bps: u16,
+    pub days_held: i64,
+    pub received: u64,
+    pub rewards_paid: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AllowlistProof {
+    pub allocation: u16,
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CooldownScope {
+    Set,
+    Property,
+    PurchaseWindow,
+    All,
+}
+
+// ========== EVENTS ==========
+
+/// Leads every event. `sequence` increases by one per event across the
+/// whole game, so indexers can detect a missed event exactly.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct EventHeader {
+    pub version: u8,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+/// Player totals after the instruction that emitted the event
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PlayerSnapshot {
+    pub total_slots_owned: u16,
+    pub properties_owned_count: u8,
+    pub complete_sets_owned: u8,
+    pub total_base_daily_income: u64,
+    pub pending_rewards: u64,
+    pub stake_boost_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AdminUpdateType {
+    Price,
+    MaxSlots,
+    Yield,
+    ShieldCost,
+    Cooldown,
+}
+
+#[event]
+pub struct GameInitializedEvent {
+    pub header: EventHeader,
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub reward_pool_vault: Pubkey,
+}
+
+#[event]
+pub struct PropertyInitializedEvent {
+    pub header: EventHeader,
+    pub property_id: u8,
+    pub set_id: u8,
+    pub max_slots_per_property: u16,
+    pub max_per_player: u16,
+    pub price: u64,
+    pub yield_percent_bps: u16,
+    pub shield_cost_percent_bps: u16,
+    pub cooldown_seconds: i64,
+}
+
+#[event]
+pub struct PlayerInitializedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct PlayerAccountClosedEvent {
+    pub header: EventHeader,
+    pub player_account: Pubkey,
+    pub closed_by: Pubkey,
+}
+
+#[event]
+pub struct GamePauseEvent {
+    pub header: EventHeader,
+    pub paused: bool,
+}
+
+#[event]
+pub struct StealChanceUpdatedEvent {
+    pub header: EventHeader,
+    pub steal_chance_bps: u16,
+}
+
+#[event]
+pub struct StealModeUpdatedEvent {
+    pub header: EventHeader,
+    pub steal_mode: u8,
+    pub max_steal_slots: u16,
+}
+
+#[event]
+pub struct DefenseParamsUpdatedEvent {
+    pub header: EventHeader,
+    pub retaliation_window_seconds: i64,
+    pub retaliation_cost_bps: u16,
+    pub insurance_premium_bps: u16,
+    pub insurance_coverage_bps: u16,
+}
+
+#[event]
+pub struct SetBonusUpdatedEvent {
+    pub header: EventHeader,
     pub set_id: u8,
-    pub max_slots_per_property: u16,
-    pub available_slots: u16,
-    pub max_per_player: u16,
-    pub price: u64,
-    pub yield_percent_bps: u16,
-    pub shield_cost_percent_bps: u16,
-    pub cooldown_seconds: i64,
-    pub bump: u8,
-    
-    pub padding: [u8; 64],
+    pub bonus_bps: u16,
 }
 
-impl Property {
-    pub const SIZE: usize = 29 + 64;
+#[event]
+pub struct GlobalRatesUpdatedEvent {
+    pub header: EventHeader,
+    pub steal_cost_percent_bps: u16,
+    pub referral_bps: u16,
 }
 
-#[account(zero_copy)]
-#[repr(C)]
-pub struct PlayerAccount {
-    pub owner: Pubkey,
-    
-    pub total_base_daily_income: u64,
-    pub last_accumulation_timestamp: i64,
-    pub total_rewards_claimed: u64,
-    pub pending_rewards: u64,
-    
-    pub total_steals_attempted: u32,
-    pub total_steals_successful: u32,
-    
-    pub total_slots_owned: u16,
-    
-    pub complete_sets_owned: u8,
-    pub properties_owned_count: u8,
-    pub bump: u8,
-    pub _padding1: [u8; 3],
-    
-    pub property_purchase_timestamp: [i64; MAX_PROPERTIES],
-    pub property_shield_expiry: [i64; MAX_PROPERTIES],
-    pub property_shield_cooldown: [i64; MAX_PROPERTIES],
-    pub property_steal_protection_expiry: [i64; MAX_PROPERTIES],
-    pub set_cooldown_timestamp: [i64; MAX_SETS],
-    pub set_cooldown_duration: [i64; MAX_SETS],
-    pub steal_cooldown_timestamp: [i64; MAX_PROPERTIES],
-    
-    pub property_slots: [u16; MAX_PROPERTIES],
-    pub property_shielded: [u16; MAX_PROPERTIES],
-    
-    pub set_last_purchased_property: [u8; MAX_SETS],
-    pub set_properties_mask: [u8; MAX_SETS],
+#[event]
+pub struct CooldownClearedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub scope: CooldownScope,
+    pub index: u8,
 }
 
-// ========== EVENTS ==========
+#[event]
+pub struct StealCooldownClearedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub property_id: u8,
+}
 
 #[event]
 pub struct PropertyBoughtEvent {
+    pub header: EventHeader,
     pub player: Pubkey,
     pub property_id: u8,
     pub price: u64,
+    pub slots: u16,
+    pub total_cost: u64,
     pub slots_owned: u16,
-    pub slots: u16,             
-    pub total_cost: u64,          
-    pub total_slots_owned: u16,  
+    pub available_slots: u16,
+    pub player_state: PlayerSnapshot,
 }
 
 #[event]
 pub struct ShieldActivatedEvent {
+    pub header: EventHeader,
     pub player: Pubkey,
     pub property_id: u8,
     pub slots_shielded: u16,
@@ -2048,64 +7023,351 @@ pub struct ShieldActivatedEvent {
 
 #[event]
 pub struct StealSuccessEvent {
+    pub header: EventHeader,
     pub attacker: Pubkey,
     pub target: Pubkey,
     pub property_id: u8,
     pub steal_cost: u64,
     pub vrf_result: u64,
+    pub slots_attempted: u16,
+    pub slots_stolen: u16,
+    pub attacker_state: PlayerSnapshot,
+    pub target_state: PlayerSnapshot,
 }
 
 #[event]
 pub struct StealFailedEvent {
+    pub header: EventHeader,
+    pub attacker: Pubkey,
+    pub target: Pubkey,
+    pub property_id: u8,
+    pub steal_cost: u64,
+    pub vrf_result: u64,
+    pub slots_attempted: u16,
+}
+
+#[event]
+pub struct RetaliationEvent {
+    pub header: EventHeader,
     pub attacker: Pubkey,
     pub target: Pubkey,
     pub property_id: u8,
     pub steal_cost: u64,
     pub vrf_result: u64,
+    pub slots_attempted: u16,
+    pub slots_stolen: u16,
+    pub attacker_state: PlayerSnapshot,
+    pub target_state: PlayerSnapshot,
+}
+
+#[event]
+pub struct InsurancePurchasedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub property_id: u8,
+    pub slots_insured: u16,
+    pub premium: u64,
+    pub expiry: i64,
+}
+
+#[event]
+pub struct InsurancePayoutEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub property_id: u8,
+    pub slots_covered: u16,
+    pub payout: u64,
+}
+
+#[event]
+pub struct InsuranceClaimedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub amount: u64,
 }
 
 #[event]
 pub struct RewardsClaimedEvent {
+    pub header: EventHeader,
     pub player: Pubkey,
     pub amount: u64,
     pub seconds_elapsed: i64,
+    pub player_state: PlayerSnapshot,
+}
+
+#[event]
+pub struct ReferralCreditEvent {
+    pub header: EventHeader,
+    pub referrer: Pubkey,
+    pub referee: Pubkey,
+    pub amount: u64,
+    pub total_referral_earnings: u64,
+}
+
+#[event]
+pub struct ReferralRewardsClaimedEvent {
+    pub header: EventHeader,
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub total_referral_earnings: u64,
 }
 
 #[event]
 pub struct PropertySoldEvent {
+    pub header: EventHeader,
     pub player: Pubkey,
     pub property_id: u8,
     pub slots: u16,
     pub received: u64,
     pub sell_value_percent: u16,
     pub days_held: i64,
+    pub slots_owned: u16,
+    pub available_slots: u16,
+    pub player_state: PlayerSnapshot,
 }
 
 #[event]
 pub struct AdminUpdateEvent {
+    pub header: EventHeader,
     pub property_id: u8,
-    pub update_type: String,
+    pub update_type: AdminUpdateType,
     pub new_value: u64,
 }
 
+#[event]
+pub struct StealProtectionUpdatedEvent {
+    pub header: EventHeader,
+    pub protection_success_seconds: i64,
+    pub protection_failed_seconds: i64,
+    pub steal_cooldown_bps: u16,
+}
+
+#[event]
+pub struct PropertyStealParamsUpdatedEvent {
+    pub header: EventHeader,
+    pub property_id: u8,
+    pub protection_success_seconds: i64,
+    pub protection_failed_seconds: i64,
+    pub steal_cooldown_seconds: i64,
+}
+
+#[event]
+pub struct RewardsVestedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub vesting_bonus: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct VestedWithdrawnEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub remaining_locked: u64,
+}
+
+#[event]
+pub struct VestingParamsUpdatedEvent {
+    pub header: EventHeader,
+    pub vesting_period_seconds: i64,
+    pub vesting_bonus_bps: u16,
+}
+
+#[event]
+pub struct TokensStakedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub lock_days: u16,
+    pub boost_bps: u16,
+    pub unlock_at: i64,
+}
+
+#[event]
+pub struct TokensUnstakedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub penalty: u64,
+    pub early: bool,
+}
+
+#[event]
+pub struct StakingParamsUpdatedEvent {
+    pub header: EventHeader,
+    pub stake_boost_unit: u64,
+    pub stake_boost_bps_per_unit_day: u16,
+    pub stake_max_boost_bps: u16,
+    pub stake_early_unlock_penalty_bps: u16,
+    pub stake_max_lock_days: u16,
+}
+
+#[event]
+pub struct SellCurveUpdatedEvent {
+    pub header: EventHeader,
+    pub sell_base_bps: u16,
+    pub sell_max_bps: u16,
+    pub sell_ramp_days: u16,
+}
+
+#[event]
+pub struct PropertySellCurveUpdatedEvent {
+    pub header: EventHeader,
+    pub property_id: u8,
+    pub sell_base_bps: u16,
+    pub sell_max_bps: u16,
+    pub sell_ramp_days: u16,
+}
+
+#[event]
+pub struct PurchaseLimitsUpdatedEvent {
+    pub header: EventHeader,
+    pub purchase_limit_flags: u8,
+    pub property_purchase_cooldown_seconds: i64,
+    pub purchase_window_seconds: i64,
+    pub purchase_window_max_slots: u16,
+}
+
+#[event]
+pub struct AltPaymentEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub payment_mint: Pubkey,
+    pub payment_amount: u64,
+    pub game_token_value: u64,
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub header: EventHeader,
+    pub account: Pubkey,
+    pub new_size: u32,
+}
+
+#[event]
+pub struct PaymentLiabilitySettledEvent {
+    pub header: EventHeader,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub remaining_owed: u64,
+}
+
+#[event]
+pub struct PaymentMintUpdatedEvent {
+    pub header: EventHeader,
+    pub mint: Pubkey,
+    pub enabled: bool,
+    pub max_staleness_seconds: i64,
+}
+
+#[event]
+pub struct PaymentRatePostedEvent {
+    pub header: EventHeader,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+#[event]
+pub struct TokensBurnedEvent {
+    pub header: EventHeader,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub total_burned: u64,
+}
+
+#[event]
+pub struct BurnRateUpdatedEvent {
+    pub header: EventHeader,
+    pub burn_bps: u16,
+}
+
+#[event]
+pub struct PlayerCrankedEvent {
+    pub header: EventHeader,
+    pub player: Pubkey,
+    pub cranker: Pubkey,
+    pub tip: u64,
+    /// Shields, protections, insurance policies and retaliation windows cleared
+    pub expired: u8,
+    pub player_state: PlayerSnapshot,
+}
+
+#[event]
+pub struct CrankParamsUpdatedEvent {
+    pub header: EventHeader,
+    pub tip_amount: u64,
+    pub min_interval_seconds: i64,
+    pub tip_budget: u64,
+}
+
+#[event]
+pub struct AccumulationTiersUpdatedEvent {
+    pub header: EventHeader,
+    pub entries: Vec<AccumulationTier>,
+}
+
+#[event]
+pub struct ConcentrationCapsUpdatedEvent {
+    pub header: EventHeader,
+    pub max_set_share_bps: u16,
+    pub max_global_share_bps: u16,
+}
+
+#[event]
+pub struct PropertyPricingUpdatedEvent {
+    pub header: EventHeader,
+    pub property_id: u8,
+    pub pricing_mode: u8,
+    pub curve_param: u64,
+}
+
+#[event]
+pub struct AllowlistUpdatedEvent {
+    pub header: EventHeader,
+    pub allowlist_root: [u8; 32],
+    pub early_access_end: i64,
+}
+
+#[event]
+pub struct PropertyLaunchEvent {
+    pub header: EventHeader,
+    pub property_id: u8,
+    pub stage: u8,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AdminGrantEvent {
+    pub header: EventHeader,
     pub admin: Pubkey,
     pub target_player: Pubkey,
     pub property_id: u8,
     pub slots: u16,
+    pub slots_owned: u16,
+    pub available_slots: u16,
+    pub player_state: PlayerSnapshot,
 }
 
 #[event]
 pub struct AdminRevokeEvent {
+    pub header: EventHeader,
     pub admin: Pubkey,
     pub target_player: Pubkey,
     pub property_id: u8,
     pub slots: u16,
+    pub slots_owned: u16,
+    pub available_slots: u16,
+    pub player_state: PlayerSnapshot,
 }
 
 #[event]
 pub struct AdminShieldGrantEvent {
+    pub header: EventHeader,
     pub admin: Pubkey,
     pub player: Pubkey,
     pub property_id: u8,
@@ -2115,6 +7377,7 @@ pub struct AdminShieldGrantEvent {
 
 #[event]
 pub struct AdminWithdrawEvent {
+    pub header: EventHeader,
     pub admin: Pubkey,
     pub amount: u64,
     pub destination: Pubkey,
@@ -2122,6 +7385,7 @@ pub struct AdminWithdrawEvent {
 
 #[event]
 pub struct AdminAuthorityTransferEvent {
+    pub header: EventHeader,
     pub old_authority: Pubkey,
     pub new_authority: Pubkey,
 }
@@ -2142,7 +7406,7 @@ pub enum ErrorCode {
     InvalidSetId,
     #[msg("No available slots for this property")]
     NoSlotsAvailable,
-    #[msg("Cooldown still active - cannot purchase yet")]
+    #[msg("Set cooldown still active - see logs for seconds remaining")]
     CooldownActive,
     #[msg("Player does not own this property")]
     DoesNotOwnProperty,
@@ -2164,7 +7428,7 @@ pub enum ErrorCode {
     Unauthorized,
     #[msg("Insufficient reward pool balance")]
     InsufficientRewardPool,
-    #[msg("Steal cooldown active - wait before attempting again")]
+    #[msg("Steal cooldown active - see logs for seconds remaining")]
     StealCooldownActive,
     #[msg("Slot hash unavailable - try again")]
     SlotHashUnavailable,
@@ -2172,7 +7436,7 @@ pub enum ErrorCode {
     InvalidShieldDuration,
     #[msg("Shield is already active. Wait for expiry before reactivating.")]
     ShieldAlreadyActive,
-    #[msg("Steal protection is active on this property")]
+    #[msg("Steal protection is active on this property - see logs for seconds remaining")]
     StealProtectionActive,
     #[msg("Invalid yield percentage (must be <= 100%)")]
     InvalidYield,
@@ -2184,6 +7448,8 @@ pub enum ErrorCode {
     InvalidStealCost,
     #[msg("Invalid bonus percentage (max 50%)")]
     InvalidBonus,
+    #[msg("Accumulation tiers must have ascending non-zero thresholds")]
+    InvalidAccumulationTiers,
     #[msg("Invalid dev wallet address")]
     InvalidDevWallet,
     #[msg("Invalid marketing wallet address")]
@@ -2196,4 +7462,407 @@ pub enum ErrorCode {
     InvalidDevTokenAccount,
     #[msg("Invalid marketing token account")]
     InvalidMarketingTokenAccount,
-}
\ No newline at end of file
+    #[msg("Cannot refer yourself")]
+    SelfReferral,
+    #[msg("Referrer was referred by this player")]
+    ReferralCycle,
+    #[msg("Referrer account does not match the player's referrer")]
+    InvalidReferrerAccount,
+    #[msg("Invalid referral rate (max 3%)")]
+    InvalidReferralRate,
+    #[msg("Invalid number of slots to steal for the current steal mode")]
+    InvalidStealSlots,
+    #[msg("Invalid steal mode")]
+    InvalidStealMode,
+    #[msg("No retaliation right against this player on this property")]
+    NoRetaliationRight,
+    #[msg("Invalid retaliation window (max 7 days)")]
+    InvalidRetaliationWindow,
+    #[msg("Steal insurance is not enabled")]
+    InsuranceDisabled,
+    #[msg("Insurance is already active on this property")]
+    InsuranceAlreadyActive,
+    #[msg("Invalid insurance duration. Must be between 1 and 168 hours.")]
+    InvalidInsuranceDuration,
+    #[msg("Invalid insurance rate")]
+    InvalidInsuranceRate,
+    #[msg("Invalid steal protection duration (max 7 days)")]
+    InvalidStealProtection,
+    #[msg("Property cooldown still active - see logs for seconds remaining")]
+    PropertyCooldownActive,
+    #[msg("Purchase budget for the current window exceeded")]
+    PurchaseBudgetExceeded,
+    #[msg("Invalid purchase limit configuration")]
+    InvalidPurchaseLimits,
+    #[msg("Set concentration cap exceeded (slots after, cap)")]
+    SetConcentrationCapExceeded,
+    #[msg("Global concentration cap exceeded (slots after, cap)")]
+    GlobalConcentrationCapExceeded,
+    #[msg("Invalid concentration cap")]
+    InvalidConcentrationCap,
+    #[msg("Invalid pricing curve")]
+    InvalidPricingCurve,
+    #[msg("Invalid sell curve")]
+    InvalidSellCurve,
+    #[msg("Staking is not enabled")]
+    StakingDisabled,
+    #[msg("Invalid stake amount")]
+    InvalidStakeAmount,
+    #[msg("Invalid lock duration")]
+    InvalidLockDuration,
+    #[msg("Invalid staking parameters")]
+    InvalidStakingParams,
+    #[msg("Vested claims are not enabled")]
+    VestingDisabled,
+    #[msg("Nothing has vested yet")]
+    NothingVested,
+    #[msg("Invalid vesting parameters")]
+    InvalidVestingParams,
+    #[msg("Invalid burn rate")]
+    InvalidBurnRate,
+    #[msg("Payment mint is disabled")]
+    PaymentMintDisabled,
+    #[msg("Invalid payment mint")]
+    InvalidPaymentMint,
+    #[msg("Invalid payment rate")]
+    InvalidPaymentRate,
+    #[msg("Payment rate is stale")]
+    StalePaymentRate,
+    #[msg("Payment exceeds the buyer's maximum")]
+    SlippageExceeded,
+    #[msg("Cost exceeds the caller's max_total_cost")]
+    MaxCostExceeded,
+    #[msg("Sale proceeds are below the caller's min_received")]
+    MinReceivedNotMet,
+    #[msg("Property launch has not started yet")]
+    LaunchNotStarted,
+    #[msg("Per-wallet cap reached for this launch")]
+    LaunchWalletCapReached,
+    #[msg("Wallet is not on the allowlist")]
+    NotAllowlisted,
+    #[msg("Allowlist allocation exceeded")]
+    AllowlistAllocationExceeded,
+    #[msg("Property has already launched")]
+    PropertyAlreadyLaunched,
+    #[msg("Invalid launch configuration")]
+    InvalidLaunchConfig,
+    #[msg("Invalid crank parameters")]
+    InvalidCrankParams,
+    #[msg("Claim pending rewards, referral rebates and insurance payouts before closing")]
+    UnclaimedBalances,
+    #[msg("Player accounts can't be closed during early access")]
+    EarlyAccessActive,
+    #[msg("Too many vested claims are still vesting")]
+    VestingTranchesFull,
+    #[msg("Reward pool is still owed for this mint's purchases")]
+    RewardPoolOwed,
+    #[msg("Settlement must be positive and no more than is owed")]
+    InvalidSettlementAmount,
+    #[msg("Account is not in the legacy layout")]
+    NotLegacyAccount,
+    #[msg("A property the player holds hasn't been migrated yet")]
+    PropertyNotMigrated,
+    #[msg("Referred players must pass their referrer's account")]
+    ReferrerAccountRequired,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86400;
+    const START: i64 = 1_700_000_000;
+    const SET_BONUS_BPS: u16 = 1000;
+
+    /// Set 0 is properties 0 and 1, each earning 1000 a day per slot
+    fn game_config() -> Box<GameConfig> {
+        let mut game_config: Box<GameConfig> = Box::new(bytemuck::Zeroable::zeroed());
+        game_config.property_income_rate = [1000; MAX_PROPERTIES];
+        game_config.property_income_updated_at = [START; MAX_PROPERTIES];
+        game_config.set_bonus_bps[0] = SET_BONUS_BPS;
+        game_config
+    }
+
+    /// Settles, then changes the holding the way the instructions do
+    fn set_holding(player: &mut PlayerAccount, game_config: &GameConfig, property_id: u8, slots: u16, now: i64) {
+        accrue_rewards(player, game_config, &[], now).unwrap();
+        player.property_slots[property_id as usize] = slots;
+        let bit = 1 << get_property_bit_in_set(property_id, 0);
+        if slots > 0 {
+            player.set_properties_mask[0] |= bit;
+        } else {
+            player.set_properties_mask[0] &= !bit;
+        }
+        refresh_set_completion(player, 0, now).unwrap();
+        player.total_base_daily_income = current_daily_income(player, game_config).unwrap();
+    }
+
+    /// Property 0 at `price` with no launch and the global steal and sell settings
+    fn property(pricing_mode: u8, price: u64, curve_param: u64) -> Property {
+        Property {
+            property_id: 0,
+            set_id: 0,
+            max_slots_per_property: 100,
+            available_slots: 100,
+            max_per_player: 100,
+            price,
+            yield_percent_bps: 0,
+            shield_cost_percent_bps: 0,
+            cooldown_seconds: 0,
+            bump: 0,
+            steal_protection_success_seconds: 0,
+            steal_protection_failed_seconds: 0,
+            steal_cooldown_seconds: 0,
+            pricing_mode,
+            curve_param,
+            launch_start_time: 0,
+            launch_end_time: 0,
+            launch_start_price: 0,
+            launch_wallet_cap: 0,
+            launch_stage: LAUNCH_NONE,
+            launch_merkle_root: [0u8; 32],
+            sell_base_bps: 0,
+            sell_max_bps: 0,
+            sell_ramp_days: 0,
+            padding: [0u8; 25],
+        }
+    }
+
+    #[test]
+    fn set_bonus_accrues_only_while_set_is_complete() {
+        let game_config = game_config();
+        let mut player: Box<PlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+
+        // Complete for the first day
+        set_holding(&mut player, &game_config, 0, 1, START);
+        set_holding(&mut player, &game_config, 1, 1, START);
+        assert_eq!(player.set_completed_at[0], START);
+        assert_eq!(player.complete_sets_owned, 1);
+
+        // Broken for the second day
+        set_holding(&mut player, &game_config, 1, 0, START + DAY);
+        assert_eq!(player.set_bonus_accrued[0], 200);
+        assert_eq!(player.set_completed_at[0], 0);
+        assert_eq!(player.complete_sets_owned, 0);
+
+        // Complete again for the third day
+        set_holding(&mut player, &game_config, 1, 1, START + 2 * DAY);
+        assert_eq!(player.set_bonus_accrued[0], 200);
+        assert_eq!(player.set_completed_at[0], START + 2 * DAY);
+        assert_eq!(player.complete_sets_owned, 1);
+
+        accrue_rewards(&mut player, &game_config, &[], START + 3 * DAY).unwrap();
+        assert_eq!(player.set_bonus_accrued[0], 400);
+        assert_eq!(player.pending_bonus_rewards, 400);
+        assert_eq!(player.pending_rewards, 2000 + 1000 + 2000);
+        assert_eq!(player.set_completed_at[0], START + 2 * DAY);
+    }
+
+    #[test]
+    fn set_bonus_ignores_income_from_before_completion() {
+        let game_config = game_config();
+        let mut player: Box<PlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+
+        set_holding(&mut player, &game_config, 0, 1, START);
+        set_holding(&mut player, &game_config, 1, 1, START + DAY);
+        assert_eq!(player.set_bonus_accrued[0], 0);
+        assert_eq!(player.set_completed_at[0], START + DAY);
+
+        accrue_rewards(&mut player, &game_config, &[], START + 2 * DAY).unwrap();
+        assert_eq!(player.pending_rewards, 1000 + 2000);
+        assert_eq!(player.set_bonus_accrued[0], 200);
+    }
+
+    #[test]
+    fn stake_boost_stops_at_unlock() {
+        let mut game_config = game_config();
+        game_config.set_bonus_bps[0] = 0;
+        let mut player: Box<PlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+        set_holding(&mut player, &game_config, 0, 1, START);
+        player.stake_boost_bps = 5000;
+        player.stake_unlock_at = START + DAY;
+
+        // Boosted for the first of the two days
+        accrue_rewards(&mut player, &game_config, &[], START + 2 * DAY).unwrap();
+        assert_eq!(player.pending_rewards, 2000 + 500);
+        assert_eq!(player.stake_boost_bps, 0);
+
+        accrue_rewards(&mut player, &game_config, &[], START + 3 * DAY).unwrap();
+        assert_eq!(player.pending_rewards, 2500 + 1000);
+    }
+
+    #[test]
+    fn new_vested_claim_keeps_older_schedule() {
+        let mut vesting = VestingAccount {
+            owner: Pubkey::default(),
+            locked_amount: 0,
+            released_amount: 0,
+            total_withdrawn: 0,
+            start_time: 0,
+            end_time: 0,
+            bump: 0,
+            tranches: [VestingTranche::default(); MAX_VESTING_TRANCHES - 1],
+        };
+        let claim = |vesting: &mut VestingAccount, now: i64| {
+            settle_vesting(vesting, now).unwrap();
+            add_vesting_tranche(vesting, 1000, now + 100).unwrap();
+        };
+
+        claim(&mut vesting, START);
+        claim(&mut vesting, START + 50);
+        assert_eq!(vesting.released_amount, 500);
+        assert_eq!(vesting.locked_amount, 1500);
+        assert_eq!(vesting.end_time, START + 100);
+
+        // The first claim still finishes on time, the second is half way
+        settle_vesting(&mut vesting, START + 100).unwrap();
+        assert_eq!(vesting.released_amount, 1000 + 500);
+        assert_eq!(vesting.locked_amount, 500);
+        assert_eq!(vesting.end_time, START + 150);
+
+        settle_vesting(&mut vesting, START + 150).unwrap();
+        assert_eq!(vesting.released_amount, 2000);
+        assert_eq!(vesting.locked_amount, 0);
+
+        // A claim after everything has vested starts from its own claim time
+        claim(&mut vesting, START + 400);
+        settle_vesting(&mut vesting, START + 450).unwrap();
+        assert_eq!(vesting.released_amount, 2500);
+    }
+
+    #[test]
+    fn migration_keeps_legacy_claim_bonuses() {
+        let mut game_config = game_config();
+        game_config.accumulation_tier1_threshold = 500;
+        game_config.accumulation_tier1_bonus_bps = 1000;
+        let mut legacy: Box<LegacyPlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+        legacy.set_properties_mask[1] = 0b011;
+
+        // Set 1 is incomplete, so only the tier above 500 pays
+        assert_eq!(legacy_claim_bonus(&legacy, 1000, &game_config).unwrap(), 50);
+
+        // Completing set 0 adds its bonus on the whole balance
+        legacy.set_properties_mask[0] = 0b11;
+        assert_eq!(legacy_claim_bonus(&legacy, 1000, &game_config).unwrap(), 50 + 100);
+        assert_eq!(legacy_claim_bonus(&legacy, 400, &game_config).unwrap(), 40);
+    }
+
+    #[test]
+    fn legacy_layouts_match_deployed_sizes() {
+        assert_eq!(std::mem::size_of::<LegacyGameConfig>(), 272);
+        assert_eq!(std::mem::size_of::<LegacyPlayerAccount>(), 1192);
+        assert_ne!(std::mem::size_of::<GameConfig>(), std::mem::size_of::<LegacyGameConfig>());
+        assert_ne!(std::mem::size_of::<PlayerAccount>(), std::mem::size_of::<LegacyPlayerAccount>());
+    }
+
+    #[test]
+    fn steal_rolls_use_seed_first_then_independent_rolls() {
+        // A single roll is the plain seed check the single-slot mode used
+        assert_eq!(count_successful_rolls(1234, 1, 1235), 1);
+        assert_eq!(count_successful_rolls(1234, 1, 1234), 0);
+
+        assert_eq!(count_successful_rolls(42, 10, 0), 0);
+        assert_eq!(count_successful_rolls(42, 10, 10000), 10);
+
+        let successes = count_successful_rolls(42, 1000, 5000);
+        assert_eq!(successes, count_successful_rolls(42, 1000, 5000));
+        assert!((400..=600).contains(&successes), "{successes} of 1000 at 50%");
+    }
+
+    #[test]
+    fn rolling_window_weights_the_previous_window() {
+        let mut game_config = game_config();
+        game_config.purchase_window_seconds = 100;
+        let mut player: Box<PlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+        player.purchase_window_start = 1000;
+        player.purchase_window_slots = 10;
+        player.purchase_window_prev_slots = 4;
+
+        // Inside the recorded window the previous one fades out linearly
+        assert_eq!(rolling_window_usage(&player, &game_config, 1000).unwrap(), 4 + 10);
+        assert_eq!(rolling_window_usage(&player, &game_config, 1050).unwrap(), 2 + 10);
+
+        // A window later the recorded window becomes the previous one
+        assert_eq!(rolling_window_usage(&player, &game_config, 1150).unwrap(), 5);
+
+        // Two windows later nothing counts
+        assert_eq!(rolling_window_usage(&player, &game_config, 1200).unwrap(), 0);
+    }
+
+    #[test]
+    fn concentration_caps_limit_set_and_global_holdings() {
+        let mut game_config = game_config();
+        game_config.set_total_supply[0] = 100;
+        game_config.total_supply = 1000;
+        game_config.max_set_share_bps = 1000;
+        game_config.max_global_share_bps = 500;
+        let caps = get_concentration_caps(&game_config, 0);
+        assert_eq!(caps, (Some(10), Some(50)));
+
+        let mut player: Box<PlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+        player.property_slots[0] = 4;
+        player.property_slots[1] = 2;
+        player.total_slots_owned = 46;
+
+        assert_eq!(concentration_room(&player, 0, caps), 4);
+        assert!(check_concentration(&player, 0, caps, 4).is_ok());
+        assert_eq!(
+            check_concentration(&player, 0, caps, 5).unwrap_err(),
+            ErrorCode::SetConcentrationCapExceeded.into()
+        );
+
+        // Another set still counts against the global cap
+        player.total_slots_owned = 48;
+        assert_eq!(concentration_room(&player, 0, caps), 2);
+        assert_eq!(
+            check_concentration(&player, 0, caps, 3).unwrap_err(),
+            ErrorCode::GlobalConcentrationCapExceeded.into()
+        );
+
+        // A zero share disables the cap
+        game_config.max_set_share_bps = 0;
+        assert_eq!(get_concentration_caps(&game_config, 0), (None, Some(50)));
+    }
+
+    #[test]
+    fn curve_cost_sums_each_slot_price() {
+        let flat = property(PRICING_FLAT, 100, 0);
+        assert_eq!(get_curve_cost(&flat, 7, 3).unwrap(), 300);
+
+        // Slots 2, 3 and 4 sold at 120, 130 and 140
+        let linear = property(PRICING_LINEAR, 100, 10);
+        assert_eq!(get_curve_cost(&linear, 2, 3).unwrap(), 390);
+        assert_eq!(get_curve_cost(&linear, 2, 0).unwrap(), 0);
+        assert_eq!(
+            get_curve_cost(&linear, 0, 5).unwrap(),
+            get_curve_cost(&linear, 0, 2).unwrap() + get_curve_cost(&linear, 2, 3).unwrap()
+        );
+
+        // 10% per slot sold: 1000, then 1100, then 1210
+        let exponential = property(PRICING_EXPONENTIAL, 1000, 1000);
+        assert_eq!(get_curve_cost(&exponential, 0, 2).unwrap(), 2100);
+        assert_eq!(get_curve_cost(&exponential, 2, 1).unwrap(), 1210);
+        assert_eq!(get_curve_cost(&property(PRICING_EXPONENTIAL, 1000, 0), 5, 2).unwrap(), 2000);
+    }
+
+    #[test]
+    fn acquisition_time_is_weighted_by_slots() {
+        let mut player: Box<PlayerAccount> = Box::new(bytemuck::Zeroable::zeroed());
+
+        record_acquisition(&mut player, 0, 2, START).unwrap();
+        assert_eq!(player.property_purchase_timestamp[0], START);
+        player.property_slots[0] = 2;
+
+        // Topping up with as many slots halves the position's age
+        record_acquisition(&mut player, 0, 2, START + 10 * DAY).unwrap();
+        assert_eq!(player.property_purchase_timestamp[0], START + 5 * DAY);
+        player.property_slots[0] = 4;
+
+        record_acquisition(&mut player, 0, 4, START + 15 * DAY).unwrap();
+        assert_eq!(player.property_purchase_timestamp[0], START + 10 * DAY);
+
+        // Nothing held and nothing added leaves it alone
+        record_acquisition(&mut player, 1, 0, START).unwrap();
+        assert_eq!(player.property_purchase_timestamp[1], 0);
+    }
+}
@@ -0,0 +1,352 @@
+// ============================================
+// FILE: defipoly-program/tests/audit-fuzz.ts
+// Property-based fuzz harness: drives random sequences of player actions
+// against a local validator and asserts the account invariants after
+// every step, both through audit_player and across accounts.
+//
+//   anchor test                         # random seed
+//   FUZZ_SEED=1234 FUZZ_STEPS=500 anchor test
+// ============================================
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import BN from "bn.js";
+import { expect } from "chai";
+import { DefipolyProgram } from "../target/types/defipoly_program";
+
+const { PublicKey, Keypair, LAMPORTS_PER_SOL, SystemProgram, SYSVAR_RENT_PUBKEY, SYSVAR_SLOT_HASHES_PUBKEY } =
+  anchor.web3;
+
+const SEED = Number(process.env.FUZZ_SEED ?? Date.now() % 2 ** 31);
+const STEPS = Number(process.env.FUZZ_STEPS ?? 150);
+const PLAYER_COUNT = 3;
+const DECIMALS = 1_000_000_000;
+
+const DEV_WALLET = new PublicKey("CgWTFX7JJQHed3qyMDjJkNCxK4sFe3wbDFABmWAAmrdS");
+const MARKETING_WALLET = new PublicKey("FoPKSQ5HDSVyZgaQobX64YEBVQ2iiKMZp8VHWtd6jLQE");
+
+// Two complete sets (2 + 3 properties) so set completion is exercised
+const PROPERTIES = [
+  { id: 0, setId: 0, price: 10 * DECIMALS, yieldBps: 100 },
+  { id: 1, setId: 0, price: 12 * DECIMALS, yieldBps: 100 },
+  { id: 2, setId: 1, price: 20 * DECIMALS, yieldBps: 90 },
+  { id: 3, setId: 1, price: 20 * DECIMALS, yieldBps: 90 },
+  { id: 4, setId: 1, price: 25 * DECIMALS, yieldBps: 90 },
+];
+const MAX_SLOTS = 40;
+const MAX_PER_PLAYER = 20;
+
+/** mulberry32: small, seedable and good enough to pick actions */
+function prng(seed: number): () => number {
+  let state = seed >>> 0;
+  return () => {
+    state = (state + 0x6d2b79f5) >>> 0;
+    let t = state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+describe("audit_player fuzz", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DefipolyProgram as Program<DefipolyProgram>;
+  const authority = (provider.wallet as anchor.Wallet).payer;
+
+  const random = prng(SEED);
+  const pick = <T>(items: T[]): T => items[Math.floor(random() * items.length)];
+  const between = (low: number, high: number) => low + Math.floor(random() * (high - low + 1));
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const gameConfig = pda(Buffer.from("game_config"));
+  const rewardPoolVault = pda(Buffer.from("reward_pool_vault"), gameConfig.toBuffer());
+  const accumulationTiers = pda(Buffer.from("accumulation_tiers"));
+  const propertyPda = (id: number) => pda(Buffer.from("property"), Buffer.from([id]));
+  const playerPda = (wallet: anchor.web3.PublicKey) => pda(Buffer.from("player"), wallet.toBuffer());
+
+  let tokenMint: anchor.web3.PublicKey;
+  let devTokenAccount: anchor.web3.PublicKey;
+  let marketingTokenAccount: anchor.web3.PublicKey;
+  const players: { keypair: anchor.web3.Keypair; tokenAccount: anchor.web3.PublicKey }[] = [];
+  const outcomes: Record<string, { ok: number; rejected: number }> = {};
+
+  before(async () => {
+    console.log(`      seed ${SEED}, ${STEPS} steps`);
+
+    tokenMint = await createMint(provider.connection, authority, authority.publicKey, null, 9);
+    devTokenAccount = anchor.utils.token.associatedAddress({ mint: tokenMint, owner: DEV_WALLET });
+    marketingTokenAccount = anchor.utils.token.associatedAddress({ mint: tokenMint, owner: MARKETING_WALLET });
+
+    await program.methods
+      .initializeGame(new BN(0))
+      .accountsPartial({
+        gameConfig,
+        tokenMint,
+        rewardPoolVault,
+        devTokenAccount,
+        devWallet: DEV_WALLET,
+        marketingTokenAccount,
+        marketingWallet: MARKETING_WALLET,
+        authority: authority.publicKey,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+    await mintTo(provider.connection, authority, tokenMint, rewardPoolVault, authority, BigInt(1_000_000 * DECIMALS));
+
+    await program.methods
+      .initializeAccumulationTiers()
+      .accountsPartial({ accumulationTiers, gameConfig, authority: authority.publicKey })
+      .rpc();
+
+    for (const property of PROPERTIES) {
+      await program.methods
+        .initializeProperty(
+          property.id,
+          property.setId,
+          MAX_SLOTS,
+          MAX_PER_PLAYER,
+          new BN(property.price),
+          property.yieldBps,
+          500,
+          new BN(0)
+        )
+        .accountsPartial({
+          property: propertyPda(property.id),
+          gameConfig,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    for (let i = 0; i < PLAYER_COUNT; i++) {
+      const keypair = Keypair.generate();
+      const airdrop = await provider.connection.requestAirdrop(keypair.publicKey, 10 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdrop, "confirmed");
+
+      const tokenAccount = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, authority, tokenMint, keypair.publicKey)
+      ).address;
+      await mintTo(provider.connection, authority, tokenMint, tokenAccount, authority, BigInt(100_000 * DECIMALS));
+
+      await program.methods
+        .initializePlayer(null, null)
+        .accountsPartial({
+          playerAccount: playerPda(keypair.publicKey),
+          gameConfig,
+          referrerAccount: null,
+          player: keypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([keypair])
+        .rpc();
+      players.push({ keypair, tokenAccount });
+    }
+  });
+
+  const propertyAccounts = () =>
+    PROPERTIES.map((property) => ({ pubkey: propertyPda(property.id), isSigner: false, isWritable: false }));
+
+  const tokenAccounts = (player: (typeof players)[number]) => ({
+    playerAccount: playerPda(player.keypair.publicKey),
+    playerTokenAccount: player.tokenAccount,
+    rewardPoolVault,
+    gameConfig,
+    tokenMint,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  // Accounts of the instructions that split a payment between the pools
+  const paymentAccounts = (player: (typeof players)[number], propertyId: number) => ({
+    ...tokenAccounts(player),
+    property: propertyPda(propertyId),
+    referrerAccount: null,
+    devTokenAccount,
+    marketingTokenAccount,
+  });
+
+  const quoteAccounts = (player: (typeof players)[number], propertyId: number) => ({
+    property: propertyPda(propertyId),
+    playerAccount: playerPda(player.keypair.publicKey),
+    gameConfig,
+    accumulationTiers,
+  });
+
+  const actions: Record<string, () => Promise<unknown>> = {
+    buy: async () => {
+      const player = pick(players);
+      const property = pick(PROPERTIES);
+      const slots = between(1, 5);
+      // Paying exactly the quoted price only fails if the quote is wrong
+      const quote = await program.methods
+        .quoteBuy(slots, [], null)
+        .accountsPartial(quoteAccounts(player, property.id))
+        .view()
+        .catch(() => null);
+      return program.methods
+        .buyProperty(slots, quote ? quote.totalCost : new BN("18446744073709551615"), [], null)
+        .accountsPartial({
+          ...paymentAccounts(player, property.id),
+          accumulationTiers,
+          player: player.keypair.publicKey,
+        })
+        .signers([player.keypair])
+        .rpc();
+    },
+    sell: async () => {
+      const player = pick(players);
+      const property = pick(PROPERTIES);
+      const slots = between(1, 5);
+      const claimRewards = random() < 0.5;
+      const quote = await program.methods
+        .quoteSell(slots, claimRewards)
+        .accountsPartial(quoteAccounts(player, property.id))
+        .view()
+        .catch(() => null);
+      return program.methods
+        .sellProperty(slots, quote ? quote.received : new BN(0), claimRewards)
+        .accountsPartial({
+          ...tokenAccounts(player),
+          property: propertyPda(property.id),
+          accumulationTiers,
+          player: player.keypair.publicKey,
+        })
+        .signers([player.keypair])
+        .rpc();
+    },
+    shield: () => {
+      const player = pick(players);
+      const property = pick(PROPERTIES);
+      return program.methods
+        .activateShield(between(1, 48), new BN("18446744073709551615"))
+        .accountsPartial({ ...paymentAccounts(player, property.id), player: player.keypair.publicKey })
+        .signers([player.keypair])
+        .rpc();
+    },
+    steal: () => {
+      const attacker = pick(players);
+      const property = pick(PROPERTIES);
+      const targets = players.filter((p) => p !== attacker);
+      const randomness = Array.from({ length: 32 }, () => between(0, 255));
+      return program.methods
+        .stealPropertyInstant(randomness, 1, new BN("18446744073709551615"))
+        .accountsPartial({
+          ...paymentAccounts(attacker, property.id),
+          accumulationTiers,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          attacker: attacker.keypair.publicKey,
+        })
+        .remainingAccounts(
+          targets.map((t) => ({ pubkey: playerPda(t.keypair.publicKey), isSigner: false, isWritable: true }))
+        )
+        .signers([attacker.keypair])
+        .rpc();
+    },
+    crank: () => {
+      const player = pick(players);
+      const cranker = pick(players);
+      return program.methods
+        .crankPlayer()
+        .accountsPartial({
+          playerAccount: playerPda(player.keypair.publicKey),
+          crankerTokenAccount: cranker.tokenAccount,
+          rewardPoolVault,
+          gameConfig,
+          tokenMint,
+          accumulationTiers,
+          cranker: cranker.keypair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([cranker.keypair])
+        .rpc();
+    },
+    claim: () => {
+      const player = pick(players);
+      return program.methods
+        .claimRewards()
+        .accountsPartial({ ...tokenAccounts(player), accumulationTiers, player: player.keypair.publicKey })
+        .signers([player.keypair])
+        .rpc();
+    },
+    updateYield: () => {
+      const property = pick(PROPERTIES);
+      return program.methods
+        .adminUpdatePropertyYield(property.id, between(50, 300))
+        .accountsPartial({ property: propertyPda(property.id), gameConfig, authority: authority.publicKey })
+        .rpc();
+    },
+    updatePrice: () => {
+      const property = pick(PROPERTIES);
+      return program.methods
+        .updatePropertyPrice(property.id, new BN(between(5, 50) * DECIMALS))
+        .accountsPartial({ property: propertyPda(property.id), gameConfig, authority: authority.publicKey })
+        .rpc();
+    },
+  };
+
+  async function assertInvariants(step: number, action: string) {
+    const context = `seed ${SEED}, step ${step} (${action})`;
+    const playerStates = [];
+
+    for (const player of players) {
+      const audit = await program.methods
+        .auditPlayer()
+        .accountsPartial({ playerAccount: playerPda(player.keypair.publicKey), gameConfig, accumulationTiers })
+        .remainingAccounts(propertyAccounts())
+        .view();
+      expect(audit.incomeChecked, context).to.equal(true);
+      expect(audit.violations, `${context}: ${JSON.stringify(audit.violations)}`).to.be.empty;
+
+      playerStates.push(await program.account.playerAccount.fetch(playerPda(player.keypair.publicKey)));
+    }
+
+    // Slots are only ever moved between players and the property's pool
+    for (const property of PROPERTIES) {
+      const state = await program.account.property.fetch(propertyPda(property.id));
+      const held = playerStates.reduce((sum, player) => sum + player.propertySlots[property.id], 0);
+      expect(held + state.availableSlots, `${context}: slot supply of property ${property.id}`).to.equal(
+        state.maxSlotsPerProperty
+      );
+    }
+  }
+
+  it("keeps player accounts consistent across random action sequences", async () => {
+    await assertInvariants(0, "setup");
+
+    for (let step = 1; step <= STEPS; step++) {
+      const action = pick(Object.keys(actions));
+      outcomes[action] ??= { ok: 0, rejected: 0 };
+      try {
+        await actions[action]();
+        outcomes[action].ok++;
+      } catch (error) {
+        // Rejected actions are expected (cooldowns, protection, nothing to
+        // sell...) and must leave the invariants intact like accepted ones
+        if (!(error instanceof anchor.AnchorError || error instanceof anchor.web3.SendTransactionError)) {
+          throw error;
+        }
+        // Buys and sells are bounded by their quotes
+        const code = error instanceof anchor.AnchorError ? error.error.errorCode.code : null;
+        expect(code, `seed ${SEED}, step ${step} (${action}): quote disagreed`).to.not.be.oneOf([
+          "MaxCostExceeded",
+          "MinReceivedNotMet",
+        ]);
+        outcomes[action].rejected++;
+      }
+      await assertInvariants(step, action);
+    }
+
+    console.log("      outcomes", JSON.stringify(outcomes));
+    expect(Object.values(outcomes).some((outcome) => outcome.ok > 0), "no action ever succeeded").to.equal(true);
+  });
+});